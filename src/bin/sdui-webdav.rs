@@ -0,0 +1,24 @@
+use rust_sdui::{chat::ChatRequest, prelude::SduiError, webdav::WebDavServer};
+
+#[tokio::main]
+async fn main() -> Result<(), SduiError> {
+    let token = std::env::var("SDUI_TOKEN").map_err(|_| SduiError::NotLoggedIn)?;
+    let port = std::env::args()
+        .nth(1)
+        .and_then(|port| port.parse().ok())
+        .unwrap_or(8080);
+
+    let (chats, _) = ChatRequest::new(&token).limit(50).request_all().await?;
+    let mut server = chats
+        .iter()
+        .fold(WebDavServer::new(&token), |server, chat| {
+            server.cloud(&chat.name, &chat.cloud)
+        });
+    if let Ok(password) = std::env::var("SDUI_WEBDAV_PASSWORD") {
+        let user = std::env::var("SDUI_WEBDAV_USER").unwrap_or_else(|_| "sdui".to_owned());
+        server = server.credentials(&user, &password);
+    }
+
+    println!("Serving WebDAV on http://localhost:{}/", port);
+    server.serve(port).await
+}
@@ -0,0 +1,126 @@
+use serde::{de::Visitor, Deserialize, Serialize};
+
+use crate::{prelude::*, timetable::Date};
+
+pub async fn get_calendar_events(
+    token: &str,
+    calendar_id: u64,
+    begin: &Date,
+    end: &Date,
+) -> SduiResult<Vec<CalendarEvent>> {
+    request(
+        &format!(
+            "https://api.sdui.app/v1/calendars/{}/events?begins_at={}&ends_at={}",
+            calendar_id, begin, end
+        ),
+        token,
+    )
+    .await
+}
+
+#[derive(Debug, Clone, Hash, Eq, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct CalendarEvent {
+    pub id: u64,
+    pub calendar_id: u64,
+    pub title: String,
+    pub description: Option<String>,
+    pub location: Option<String>,
+    pub begins_at: u64,
+    pub ends_at: u64,
+    #[serde(default)]
+    pub is_all_day: bool,
+    #[serde(rename = "type", default)]
+    pub kind: CalendarEventKind,
+}
+
+impl CalendarEvent {
+    pub fn first_day(&self) -> Date {
+        Date::from_timestamp(self.begins_at)
+    }
+
+    pub fn last_day(&self) -> Date {
+        Date::from_timestamp(self.ends_at.max(self.begins_at + 1) - 1)
+    }
+}
+
+#[derive(Debug, Clone, Default, Hash, Eq, PartialEq, PartialOrd, Ord)]
+pub enum CalendarEventKind {
+    #[default]
+    EVENT,
+    EXAM,
+    HOLIDAY,
+    Other(String),
+}
+
+impl CalendarEventKind {
+    pub fn as_str(&self) -> &str {
+        match self {
+            CalendarEventKind::EVENT => "EVENT",
+            CalendarEventKind::EXAM => "EXAM",
+            CalendarEventKind::HOLIDAY => "HOLIDAY",
+            CalendarEventKind::Other(kind) => kind,
+        }
+    }
+}
+
+impl From<&str> for CalendarEventKind {
+    fn from(kind: &str) -> Self {
+        match kind.to_ascii_uppercase().as_str() {
+            "" | "EVENT" | "APPOINTMENT" => CalendarEventKind::EVENT,
+            "EXAM" | "TEST" | "CLASSTEST" => CalendarEventKind::EXAM,
+            "HOLIDAY" | "HOLIDAYS" | "VACATION" => CalendarEventKind::HOLIDAY,
+            _ => CalendarEventKind::Other(kind.to_owned()),
+        }
+    }
+}
+
+impl Serialize for CalendarEventKind {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for CalendarEventKind {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        struct CalendarEventKindVisitor;
+
+        impl<'de> Visitor<'de> for CalendarEventKindVisitor {
+            type Value = CalendarEventKind;
+
+            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+                formatter.write_str("Expected none or an event kind like EXAM")
+            }
+            fn visit_none<E>(self) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                Ok(CalendarEventKind::EVENT)
+            }
+            fn visit_unit<E>(self) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                Ok(CalendarEventKind::EVENT)
+            }
+            fn visit_some<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+            where
+                D: serde::Deserializer<'de>,
+            {
+                deserializer.deserialize_str(CalendarEventKindVisitor)
+            }
+            fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                Ok(CalendarEventKind::from(v))
+            }
+        }
+        deserializer.deserialize_option(CalendarEventKindVisitor {})
+    }
+}
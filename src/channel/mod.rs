@@ -1,5 +1,12 @@
 use serde::{Deserialize, Serialize};
 
+use crate::{
+    chat::Message,
+    files::{open_first, DownloadStream},
+    news::News,
+    prelude::*,
+};
+
 #[derive(Debug, Clone, Hash, Eq, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct Channel {
     pub meta: ChannelMeta,
@@ -63,9 +70,61 @@ pub struct Attachment {
     pub uuid: String,
 }
 
+impl Attachment {
+    pub async fn open(&self, token: &str) -> Result<DownloadStream, SduiError> {
+        match open_first(&self.meta.download_uris(), token).await {
+            Err(SduiError::Expired) => {
+                let (attachment, _) = self.refresh(token).await?;
+                open_first(&attachment.meta.download_uris(), token).await
+            }
+            result => result,
+        }
+    }
+
+    pub async fn refresh(&self, token: &str) -> SduiResult<Attachment> {
+        let (attachments, rate_limit) = match self.source_type.to_ascii_lowercase().as_str() {
+            "news" => {
+                let (news, rate_limit) = request::<News>(
+                    &format!("https://api.sdui.app/v1/news/{}", self.source_id),
+                    token,
+                )
+                .await?;
+                (news.attachments, rate_limit)
+            }
+            "message" | "chat" => {
+                let (message, rate_limit) = request::<Message>(
+                    &format!("https://api.sdui.app/v1/messages/{}", self.source_id),
+                    token,
+                )
+                .await?;
+                (message.attachments, rate_limit)
+            }
+            _ => {
+                let (attachment, rate_limit) = request::<Attachment>(
+                    &format!("https://api.sdui.app/v1/attachments/{}", self.id),
+                    token,
+                )
+                .await?;
+                (vec![attachment], rate_limit)
+            }
+        };
+        attachments
+            .into_iter()
+            .find(|attachment| attachment.id == self.id)
+            .map(|attachment| (attachment, rate_limit))
+            .ok_or(SduiError::Expired)
+    }
+}
+
 #[derive(Debug, Clone, Hash, Eq, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct AttachmentMeta {
     pub download_uri: String,
     pub temp_uri: String,
     pub uri: String,
 }
+
+impl AttachmentMeta {
+    pub fn download_uris(&self) -> [&str; 3] {
+        [&self.temp_uri, &self.download_uri, &self.uri]
+    }
+}
@@ -0,0 +1,180 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{channel::Attachment, prelude::*, user::PartialSduiUser};
+
+pub struct MessageRequest {
+    token: String,
+    chat_id: u64,
+    limit: u64,
+    before: Option<u64>,
+}
+
+pub struct MessageHistory {
+    request: MessageRequest,
+    done: bool,
+}
+
+impl MessageRequest {
+    pub fn new(token: &str, chat_id: u64) -> Self {
+        MessageRequest {
+            token: token.to_owned(),
+            chat_id,
+            limit: 25,
+            before: None,
+        }
+    }
+
+    pub fn limit(mut self, limit: u64) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    pub fn before(mut self, message_id: u64) -> Self {
+        self.before = Some(message_id);
+        self
+    }
+
+    pub async fn request(&self) -> SduiResult<Vec<Message>> {
+        let mut url = format!(
+            "https://api.sdui.app/v1/channels/{}/messages?limit={}",
+            self.chat_id, self.limit
+        );
+        if let Some(before) = self.before {
+            url.push_str(&format!("&before={}", before));
+        }
+        request(&url, &self.token).await
+    }
+
+    pub fn history(self) -> MessageHistory {
+        MessageHistory {
+            request: MessageRequest {
+                limit: self.limit.max(1),
+                ..self
+            },
+            done: false,
+        }
+    }
+}
+
+impl MessageHistory {
+    pub fn cursor(&self) -> Option<u64> {
+        self.request.before
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.done
+    }
+
+    pub async fn next_page(&mut self) -> Option<SduiResult<Vec<Message>>> {
+        if self.done {
+            return None;
+        }
+        let result = self.request.request().await;
+        match &result {
+            Ok((messages, _)) => self.advance(messages),
+            Err(_) => self.done = true,
+        }
+        Some(result)
+    }
+
+    fn advance(&mut self, messages: &[Message]) {
+        match messages.iter().map(|message| message.id).min() {
+            Some(oldest)
+                if messages.len() as u64 >= self.request.limit
+                    && self.request.before.is_none_or(|before| oldest < before) =>
+            {
+                self.request.before = Some(oldest)
+            }
+            _ => self.done = true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Hash, Eq, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct Message {
+    #[serde(default)]
+    pub attachments: Vec<Attachment>,
+    pub chat_id: u64,
+    pub content: String,
+    pub content_rendered: String,
+    pub created_at: String,
+    pub deleted_at: Option<String>,
+    pub edited_at: Option<String>,
+    pub id: u64,
+    #[serde(default)]
+    pub is_deleted: bool,
+    #[serde(default)]
+    pub is_edited: bool,
+    pub parent: Option<Box<Message>>,
+    pub parent_id: Option<u64>,
+    #[serde(default)]
+    pub reactions: Vec<Reaction>,
+    #[serde(default)]
+    pub replies_count: u64,
+    pub updated_at: Option<String>,
+    pub user: Option<PartialSduiUser>,
+    pub user_id: Option<u64>,
+    pub uuid: String,
+}
+
+#[derive(Debug, Clone, Hash, Eq, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct Reaction {
+    pub count: u64,
+    pub emoji: String,
+    #[serde(default)]
+    pub has_reacted: bool,
+    #[serde(default)]
+    pub user_ids: Vec<u64>,
+}
+
+impl Message {
+    pub fn is_reply(&self) -> bool {
+        self.parent_id.is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(id: u64) -> Message {
+        serde_json::from_value(serde_json::json!({
+            "chat_id": 3,
+            "content": "Hallo",
+            "content_rendered": "<p>Hallo</p>",
+            "created_at": "2023-05-08 08:00:00",
+            "deleted_at": null,
+            "edited_at": null,
+            "id": id,
+            "parent": null,
+            "parent_id": null,
+            "reactions": [{ "count": 2, "emoji": "👍" }],
+            "updated_at": null,
+            "user": null,
+            "user_id": 7,
+            "uuid": id.to_string(),
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_message_history() {
+        let hello = message(12);
+        assert_eq!(hello.reactions[0].count, 2);
+        assert!(hello.attachments.is_empty() && !hello.is_edited && !hello.is_reply());
+
+        let mut history = MessageRequest::new("", 3).limit(2).history();
+        assert_eq!(history.cursor(), None);
+        history.advance(&[message(12), message(11)]);
+        assert_eq!(history.cursor(), Some(11));
+        assert!(!history.is_done());
+        history.advance(&[message(12), message(11)]);
+        assert!(history.is_done());
+        assert_eq!(history.cursor(), Some(11));
+
+        let mut history = MessageRequest::new("", 3).limit(2).history();
+        history.advance(&[message(12), message(11)]);
+        history.advance(&[message(10)]);
+        assert!(history.is_done());
+    }
+}
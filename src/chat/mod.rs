@@ -1,7 +1,16 @@
+use std::collections::HashSet;
+
 use serde::{Deserialize, Serialize};
 
 use crate::{cloud::Cloud, prelude::*};
 
+mod message;
+mod send;
+mod state;
+pub use crate::chat::message::*;
+pub use crate::chat::send::*;
+pub use crate::chat::state::*;
+
 pub struct ChatRequest {
     token: String,
     page: u64,
@@ -42,15 +51,57 @@ impl ChatRequest {
     }
 
     pub async fn request(&self) -> SduiResult<Vec<Chat>> {
-        request(
-            &format!(
-                "https://api.sdui.app/v1/users/self/channels/chats?&with_archived={}&page={}&search={}&limit={}",
-                self.with_archived, self.page, self.search, self.limit
-            ),
-            &self.token,
+        let (response, rate_limit) = self.request_page().await?;
+        Ok((response.data, rate_limit))
+    }
+
+    pub async fn request_all(&self) -> SduiResult<Vec<Chat>> {
+        let mut request = ChatRequest {
+            token: self.token.clone(),
+            page: self.page,
+            limit: self.limit.max(1),
+            with_archived: self.with_archived,
+            search: self.search.clone(),
+        };
+        let mut chats = vec![];
+        let mut ids = HashSet::new();
+        let (mut response, mut rate_limit) = request.request_page().await?;
+        while request.merge_page(&mut chats, &mut ids, response) {
+            request.page += 1;
+            let (page, page_rate_limit) = request.request_page().await?;
+            rate_limit = rate_limit.join(page_rate_limit);
+            response = page;
+        }
+        Ok((chats, rate_limit))
+    }
+
+    async fn request_page(&self) -> SduiResult<SduiResponse<Vec<Chat>>> {
+        send_response(
+            CLIENT
+                .get(format!(
+                    "https://api.sdui.app/v1/users/self/channels/chats?&with_archived={}&page={}&search={}&limit={}",
+                    self.with_archived, self.page, self.search, self.limit
+                ))
+                .bearer_auth(&self.token),
         )
         .await
     }
+
+    fn merge_page(
+        &self,
+        chats: &mut Vec<Chat>,
+        ids: &mut HashSet<u64>,
+        response: SduiResponse<Vec<Chat>>,
+    ) -> bool {
+        let full = response.data.len() as u64 >= self.limit;
+        let before = chats.len();
+        chats.extend(response.data.into_iter().filter(|chat| ids.insert(chat.id)));
+        full && chats.len() > before
+            && response
+                .meta
+                .last_page
+                .is_none_or(|last_page| self.page < last_page)
+    }
 }
 
 pub async fn get_chat(token: &str, id: &u64) -> SduiResult<Chat> {
@@ -175,3 +226,142 @@ pub struct ChatMeta {
 
 #[derive(Debug, Clone, Hash, Eq, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct NextPossibleKnock {}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::cloud::{CloudCan, CloudMeta};
+
+    pub(crate) fn chat(id: u64) -> Chat {
+        Chat {
+            activity_at: "2023-05-08 08:00:00".to_owned(),
+            admin_ids: vec![],
+            avatar: None,
+            calendar_id: 1,
+            can: ChatCan {
+                create_survey: 0,
+                delete: 0,
+                delete_message_history: 0,
+                knock: 0,
+                leave: 1,
+                manage_admins: 0,
+                move_channel_content: 0,
+                manage_users: 0,
+                pin: 1,
+                start_conference: 0,
+                toggle_memberlist: 0,
+                toggle_state: 0,
+                toggle_twoway: 0,
+                update: 0,
+                view_users: 1,
+                voice_memo: 1,
+            },
+            chat: PartialChat {
+                can: PartialChatCan {
+                    post_message: 1,
+                    toggle_oneway: 0,
+                },
+                disabled_at: None,
+                id,
+                meta: vec![],
+                updated_at: "2023-05-08 08:00:00".to_owned(),
+            },
+            chat_id: id,
+            cloud: Cloud {
+                can: CloudCan {
+                    upload: 1,
+                    create_protected_folder: 0,
+                },
+                disabled_at: None,
+                id: 1,
+                meta: CloudMeta {
+                    download: None,
+                    forbidden: vec!["exe".to_owned()],
+                    max_number: 10,
+                    rename: None,
+                    upload: None,
+                    upload_limit: Some(1024),
+                },
+                updated_at: None,
+            },
+            cloud_id: 1,
+            code: "abc".to_owned(),
+            color: None,
+            content_move_decision_mate_at: None,
+            created_at: "2023-05-08 08:00:00".to_owned(),
+            description: None,
+            description_members: None,
+            disabled_by_id: None,
+            expiration_reason: None,
+            expires_at: None,
+            group: None,
+            icon: None,
+            id,
+            intern_id: None,
+            is_disabled: false,
+            is_hidden_memberlist: false,
+            is_leavable: true,
+            is_public: false,
+            is_twoway: true,
+            meta: ChatMeta {
+                description: String::new(),
+                displayname: "Klasse 7a".to_owned(),
+                is_archived: 0,
+                is_movable: false,
+                is_muted: false,
+                is_official: false,
+                is_paused: false,
+                is_pinned: false,
+                is_unread: false,
+                languages: vec![],
+                last_knocked_at: None,
+                last_unread_count: 0,
+                next_possible_knock: None,
+                read_at: "2023-05-08 08:00:00".to_owned(),
+                shortcut: "7a".to_owned(),
+                subtitle: None,
+            },
+            name: "Klasse 7a".to_owned(),
+            school: School::from_value(&serde_json::json!({
+                "id": 1, "name": "Schule", "name_alias": null, "slink": "schule", "uuid": "1"
+            }))
+            .unwrap(),
+            school_id: 1,
+            subtitle: None,
+            target: None,
+            trashed_at: None,
+            twoway_expires_at: None,
+            chat_type: "chat".to_owned(),
+            updated_at: "2023-05-08 08:00:00".to_owned(),
+            user_id: None,
+            users_count: 25,
+            uuid: id.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_request_all_pages() {
+        let page = |ids: &[u64], last_page: Option<u64>| SduiResponse {
+            data: ids.iter().map(|id| chat(*id)).collect(),
+            status: "SUCCESS".to_owned(),
+            meta: SduiMeta {
+                warnings: serde_json::Value::Null,
+                errors: serde_json::Value::Null,
+                success: serde_json::Value::Null,
+                last_page,
+            },
+        };
+        let mut request = ChatRequest::new("").limit(2);
+        let mut chats = vec![];
+        let mut ids = HashSet::new();
+        assert!(request.merge_page(&mut chats, &mut ids, page(&[1, 2], None)));
+        request.page += 1;
+        assert!(!request.merge_page(&mut chats, &mut ids, page(&[1, 2], None)));
+        assert!(!request.merge_page(&mut chats, &mut ids, page(&[3, 4], Some(2))));
+        assert!(!request.merge_page(&mut chats, &mut ids, page(&[5], None)));
+        assert_eq!(
+            chats.iter().map(|chat| chat.id).collect::<Vec<_>>(),
+            [1, 2, 3, 4, 5]
+        );
+    }
+}
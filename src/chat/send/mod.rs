@@ -0,0 +1,212 @@
+use std::path::PathBuf;
+
+use bytes::Bytes;
+use serde::Serialize;
+
+use crate::{
+    chat::{Chat, Message},
+    cloud::UploadRequest,
+    files::File,
+    prelude::*,
+};
+
+pub struct SendMessage {
+    token: String,
+    chat: Chat,
+    content: String,
+    parent_id: Option<u64>,
+    attachments: Vec<PendingUpload>,
+    voice_memo: Option<PendingUpload>,
+}
+
+#[allow(clippy::large_enum_variant)]
+enum PendingUpload {
+    File(File),
+    Bytes(String, Bytes),
+    Path(PathBuf),
+}
+
+#[derive(Serialize)]
+struct MessageBody<'a> {
+    content: &'a str,
+    parent_id: Option<u64>,
+    attachments: Vec<String>,
+    voice_memo: Option<String>,
+}
+
+impl SendMessage {
+    pub fn new(token: &str, chat: &Chat) -> Self {
+        SendMessage {
+            token: token.to_owned(),
+            chat: chat.clone(),
+            content: String::new(),
+            parent_id: None,
+            attachments: vec![],
+            voice_memo: None,
+        }
+    }
+
+    pub fn content(mut self, content: &str) -> Self {
+        self.content = content.to_owned();
+        self
+    }
+
+    pub fn reply_to(mut self, message: &Message) -> Self {
+        self.parent_id = Some(message.id);
+        self
+    }
+
+    pub fn file(mut self, file: &File) -> Self {
+        self.attachments.push(PendingUpload::File(file.clone()));
+        self
+    }
+
+    pub fn attachment(mut self, name: &str, data: impl Into<Bytes>) -> Self {
+        self.attachments
+            .push(PendingUpload::Bytes(name.to_owned(), data.into()));
+        self
+    }
+
+    pub fn attachment_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.attachments.push(PendingUpload::Path(path.into()));
+        self
+    }
+
+    pub fn voice_memo(mut self, name: &str, data: impl Into<Bytes>) -> Self {
+        self.voice_memo = Some(PendingUpload::Bytes(name.to_owned(), data.into()));
+        self
+    }
+
+    pub fn validate(&self) -> Result<(), SduiError> {
+        if self.chat.chat.can.post_message == 0
+            || (!self.chat.is_twoway && self.chat.can.update == 0)
+            || (self.voice_memo.is_some() && self.chat.can.voice_memo == 0)
+        {
+            return Err(SduiError::PermissionDenied);
+        }
+        if self.content.trim().is_empty()
+            && self.attachments.is_empty()
+            && self.voice_memo.is_none()
+        {
+            return Err(SduiError::EmptyMessage);
+        }
+        for upload in self.attachments.iter().chain(&self.voice_memo) {
+            match upload {
+                PendingUpload::Bytes(name, data) => {
+                    self.chat.cloud.validate_upload(name, data.len() as u64)?
+                }
+                PendingUpload::File(file) if file.cloud_id != self.chat.cloud_id => {
+                    return Err(SduiError::InvalidTarget(file.name.clone()))
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    pub async fn send(&self) -> SduiResult<Message> {
+        self.validate()?;
+        let mut rate_limit = None;
+        let mut attachments = Vec::with_capacity(self.attachments.len());
+        for upload in &self.attachments {
+            attachments.push(self.upload(upload, &mut rate_limit).await?);
+        }
+        let voice_memo = match &self.voice_memo {
+            Some(upload) => Some(self.upload(upload, &mut rate_limit).await?),
+            None => None,
+        };
+        let (message, message_rate_limit) = send::<Message>(
+            CLIENT
+                .post(format!(
+                    "https://api.sdui.app/v1/channels/{}/messages",
+                    self.chat.chat_id
+                ))
+                .bearer_auth(&self.token)
+                .json(&MessageBody {
+                    content: &self.content,
+                    parent_id: self.parent_id,
+                    attachments,
+                    voice_memo,
+                }),
+        )
+        .await?;
+        join_rate_limit(&mut rate_limit, message_rate_limit);
+        Ok((message, rate_limit.unwrap_or_else(RateLimit::unknown)))
+    }
+
+    async fn upload(
+        &self,
+        upload: &PendingUpload,
+        rate_limit: &mut Option<RateLimit>,
+    ) -> Result<String, SduiError> {
+        let request = UploadRequest::new(&self.token, &self.chat.cloud);
+        let (file, upload_rate_limit) = match upload {
+            PendingUpload::File(file) => return Ok(file.uuid.clone()),
+            PendingUpload::Bytes(name, data) => request.upload_bytes(name, data.clone()).await?,
+            PendingUpload::Path(path) => request.upload_path(path).await?,
+        };
+        join_rate_limit(rate_limit, upload_rate_limit);
+        Ok(file.uuid)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{chat::tests::chat, files::tests::file};
+
+    #[test]
+    fn test_validate_message() {
+        let mut class = chat(3);
+        assert!(matches!(
+            SendMessage::new("", &class).validate(),
+            Err(SduiError::EmptyMessage)
+        ));
+        assert!(SendMessage::new("", &class)
+            .content("Hallo")
+            .voice_memo("memo.m4a", vec![0; 16])
+            .validate()
+            .is_ok());
+        assert!(matches!(
+            SendMessage::new("", &class)
+                .attachment("setup.exe", vec![0; 16])
+                .validate(),
+            Err(SduiError::ForbiddenExtension(_))
+        ));
+        let mut foreign = file("Gedicht.pdf", "file");
+        foreign.cloud_id = class.cloud_id + 1;
+        assert!(matches!(
+            SendMessage::new("", &class).file(&foreign).validate(),
+            Err(SduiError::InvalidTarget(_))
+        ));
+        foreign.cloud_id = class.cloud_id;
+        assert!(SendMessage::new("", &class)
+            .file(&foreign)
+            .validate()
+            .is_ok());
+
+        class.can.voice_memo = 0;
+        assert!(matches!(
+            SendMessage::new("", &class)
+                .voice_memo("memo.m4a", vec![0; 16])
+                .validate(),
+            Err(SduiError::PermissionDenied)
+        ));
+        class.is_twoway = false;
+        let message = SendMessage::new("", &class).content("Hallo");
+        assert!(matches!(
+            message.validate(),
+            Err(SduiError::PermissionDenied)
+        ));
+        class.can.update = 1;
+        assert!(SendMessage::new("", &class)
+            .content("Hallo")
+            .validate()
+            .is_ok());
+        class.chat.can.post_message = 0;
+        assert!(matches!(
+            SendMessage::new("", &class).content("Hallo").validate(),
+            Err(SduiError::PermissionDenied)
+        ));
+    }
+}
@@ -0,0 +1,136 @@
+use crate::{
+    chat::{Chat, Message},
+    prelude::*,
+};
+
+#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq, PartialOrd, Ord)]
+pub enum ChatAction {
+    READ,
+    MUTE,
+    UNMUTE,
+    PIN,
+    UNPIN,
+    ARCHIVE,
+    UNARCHIVE,
+    LEAVE,
+}
+
+impl ChatAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ChatAction::READ => "read",
+            ChatAction::MUTE => "mute",
+            ChatAction::UNMUTE => "unmute",
+            ChatAction::PIN => "pin",
+            ChatAction::UNPIN => "unpin",
+            ChatAction::ARCHIVE => "archive",
+            ChatAction::UNARCHIVE => "unarchive",
+            ChatAction::LEAVE => "leave",
+        }
+    }
+}
+
+pub async fn mark_all_chats_read(token: &str) -> SduiResult<()> {
+    send_empty(
+        CLIENT
+            .post("https://api.sdui.app/v1/users/self/channels/chats/read")
+            .bearer_auth(token),
+    )
+    .await
+}
+
+impl Chat {
+    pub fn can_perform(&self, action: ChatAction) -> bool {
+        match action {
+            ChatAction::READ | ChatAction::MUTE | ChatAction::UNMUTE => true,
+            ChatAction::PIN | ChatAction::UNPIN => self.can.pin != 0,
+            ChatAction::ARCHIVE | ChatAction::UNARCHIVE => self.can.toggle_state != 0,
+            ChatAction::LEAVE => self.can.leave != 0 && self.is_leavable,
+        }
+    }
+
+    pub async fn mark_read(&self, token: &str, message: Option<&Message>) -> SduiResult<Chat> {
+        let mut request = self.action_request(token, ChatAction::READ)?;
+        if let Some(message) = message {
+            request = request.query(&[("message_id", message.id)]);
+        }
+        send(request).await
+    }
+
+    pub async fn mute(&self, token: &str) -> SduiResult<Chat> {
+        send(self.action_request(token, ChatAction::MUTE)?).await
+    }
+
+    pub async fn unmute(&self, token: &str) -> SduiResult<Chat> {
+        send(self.action_request(token, ChatAction::UNMUTE)?).await
+    }
+
+    pub async fn pin(&self, token: &str) -> SduiResult<Chat> {
+        send(self.action_request(token, ChatAction::PIN)?).await
+    }
+
+    pub async fn unpin(&self, token: &str) -> SduiResult<Chat> {
+        send(self.action_request(token, ChatAction::UNPIN)?).await
+    }
+
+    pub async fn archive(&self, token: &str) -> SduiResult<Chat> {
+        send(self.action_request(token, ChatAction::ARCHIVE)?).await
+    }
+
+    pub async fn unarchive(&self, token: &str) -> SduiResult<Chat> {
+        send(self.action_request(token, ChatAction::UNARCHIVE)?).await
+    }
+
+    pub async fn leave(&self, token: &str) -> SduiResult<Chat> {
+        send(self.action_request(token, ChatAction::LEAVE)?).await
+    }
+
+    fn action_request(
+        &self,
+        token: &str,
+        action: ChatAction,
+    ) -> Result<reqwest::RequestBuilder, SduiError> {
+        if !self.can_perform(action) {
+            return Err(SduiError::PermissionDenied);
+        }
+        Ok(CLIENT
+            .post(format!(
+                "https://api.sdui.app/v1/users/self/channels/{}/{}",
+                self.id,
+                action.as_str()
+            ))
+            .bearer_auth(token))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chat::tests::chat;
+
+    #[test]
+    fn test_chat_actions() {
+        let mut class = chat(3);
+        assert!(class.can_perform(ChatAction::READ));
+        assert!(class.can_perform(ChatAction::PIN));
+        assert!(class.can_perform(ChatAction::LEAVE));
+        assert!(!class.can_perform(ChatAction::ARCHIVE));
+        assert!(matches!(
+            class.action_request("", ChatAction::UNARCHIVE),
+            Err(SduiError::PermissionDenied)
+        ));
+
+        class.is_leavable = false;
+        class.can.toggle_state = 1;
+        assert!(!class.can_perform(ChatAction::LEAVE));
+        let request = class
+            .action_request("", ChatAction::ARCHIVE)
+            .unwrap()
+            .build()
+            .unwrap();
+        assert_eq!(
+            request.url().as_str(),
+            "https://api.sdui.app/v1/users/self/channels/3/archive"
+        );
+    }
+}
@@ -1,6 +1,17 @@
-use crate::prelude::*;
+use crate::{files::File, prelude::*};
 use serde::{Deserialize, Serialize};
 
+mod report;
+mod search;
+mod sync;
+mod tree;
+mod upload;
+pub use crate::cloud::report::*;
+pub use crate::cloud::search::*;
+pub use crate::cloud::sync::*;
+pub use crate::cloud::tree::*;
+pub use crate::cloud::upload::*;
+
 pub async fn get_cloud(token: &str, id: u64) -> SduiResult<Cloud> {
     request(
         &format!("https://api.sdui.app/v1/users/self/channels/cloud/{}", id),
@@ -18,6 +29,19 @@ pub struct Cloud {
     pub updated_at: Option<String>,
 }
 
+impl Cloud {
+    pub async fn content(&self, token: &str) -> SduiResult<Vec<File>> {
+        request(
+            &format!(
+                "https://api.sdui.app/v1/users/self/channels/cloud/{}/files",
+                self.id
+            ),
+            token,
+        )
+        .await
+    }
+}
+
 #[derive(Debug, Clone, Hash, Eq, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct CloudCan {
     pub upload: u8,
@@ -0,0 +1,393 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    cloud::{Cloud, CloudTree, TreeNode},
+    files::File,
+    prelude::*,
+    timetable::Date,
+};
+
+const AGE_BUCKETS: [(u64, &str); 4] = [
+    (30, "< 30 days"),
+    (180, "30-180 days"),
+    (365, "180-365 days"),
+    (u64::MAX, "> 1 year"),
+];
+
+pub async fn get_storage_report(
+    token: &str,
+    clouds: &[(String, Cloud)],
+) -> SduiResult<StorageReport> {
+    let mut trees = vec![];
+    let mut rate_limit = None;
+    for (name, cloud) in clouds {
+        let mut tree = CloudTree::new(cloud);
+        let (_, tree_rate_limit) = tree.load_all(token).await?;
+        join_rate_limit(&mut rate_limit, tree_rate_limit);
+        trees.push((name.clone(), tree));
+    }
+    Ok((
+        StorageReport::from_trees(&trees, &Date::today()),
+        rate_limit.unwrap_or_else(RateLimit::unknown),
+    ))
+}
+
+#[derive(Debug, Clone, Default, Hash, Eq, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct Usage {
+    pub key: String,
+    pub files: u64,
+    pub size: u64,
+}
+
+#[derive(Debug, Clone, Hash, Eq, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct Duplicate {
+    pub hash: String,
+    pub size: u64,
+    pub paths: Vec<String>,
+}
+
+impl Duplicate {
+    pub fn wasted(&self) -> u64 {
+        self.size * (self.paths.len() as u64).saturating_sub(1)
+    }
+}
+
+#[derive(Debug, Clone, Hash, Eq, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct ExpiringFile {
+    pub path: String,
+    pub size: u64,
+    pub expires_at: String,
+}
+
+#[derive(Debug, Clone, Default, Hash, Eq, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct StorageReport {
+    pub files: u64,
+    pub size: u64,
+    pub clouds: Vec<Usage>,
+    pub folders: Vec<Usage>,
+    pub uploaders: Vec<Usage>,
+    pub kinds: Vec<Usage>,
+    pub ages: Vec<Usage>,
+    pub duplicates: Vec<Duplicate>,
+    pub expiring: Vec<ExpiringFile>,
+}
+
+impl StorageReport {
+    pub fn from_trees(trees: &[(String, CloudTree)], today: &Date) -> Self {
+        let mut files = vec![];
+        for (name, tree) in trees {
+            collect(
+                tree.children.iter().flatten(),
+                name,
+                &format!("/{}", name),
+                &mut files,
+            );
+        }
+        let mut report = StorageReport::default();
+        let mut clouds = BTreeMap::new();
+        let mut folders = BTreeMap::new();
+        let mut uploaders = BTreeMap::new();
+        let mut kinds = BTreeMap::new();
+        let mut ages = BTreeMap::new();
+        let mut hashes: BTreeMap<&str, Vec<&(String, String, &File)>> = BTreeMap::new();
+        for entry in &files {
+            let (cloud, folder, file) = entry;
+            report.files += 1;
+            report.size += file.size;
+            add(&mut clouds, cloud.clone(), file);
+            add(&mut folders, folder.clone(), file);
+            add(&mut uploaders, uploader(file), file);
+            add(&mut kinds, file.kind().as_str().to_owned(), file);
+            add(&mut ages, age(file, today).to_owned(), file);
+            if let Some(hash) = file.hash.as_deref().filter(|hash| !hash.is_empty()) {
+                hashes.entry(hash).or_default().push(entry);
+            }
+            if let Some(expires_at) = file
+                .expires_at
+                .as_ref()
+                .filter(|expires_at| !is_expired(expires_at, today))
+            {
+                report.expiring.push(ExpiringFile {
+                    path: format!("{}/{}", folder, file.name),
+                    size: file.size,
+                    expires_at: expires_at.clone(),
+                });
+            }
+        }
+        report.clouds = sorted(clouds);
+        report.folders = sorted(folders);
+        report.uploaders = sorted(uploaders);
+        report.kinds = sorted(kinds);
+        report.ages = AGE_BUCKETS
+            .iter()
+            .filter_map(|(_, label)| ages.remove(*label))
+            .collect();
+        report.duplicates = hashes
+            .into_iter()
+            .filter(|(_, entries)| entries.len() > 1)
+            .map(|(hash, entries)| Duplicate {
+                hash: hash.to_owned(),
+                size: entries[0].2.size,
+                paths: entries
+                    .iter()
+                    .map(|(_, folder, file)| format!("{}/{}", folder, file.name))
+                    .collect(),
+            })
+            .collect();
+        report
+            .duplicates
+            .sort_by_key(|duplicate| std::cmp::Reverse(duplicate.wasted()));
+        report
+            .expiring
+            .sort_by(|a, b| a.expires_at.cmp(&b.expires_at));
+        report
+    }
+
+    pub fn wasted(&self) -> u64 {
+        self.duplicates.iter().map(Duplicate::wasted).sum()
+    }
+
+    pub fn to_json(&self) -> Result<String, SduiError> {
+        serde_json::to_string_pretty(self).map_err(|_| SduiError::JSONError)
+    }
+
+    pub fn usage_csv(&self) -> String {
+        let mut csv = String::from("category,key,files,size\n");
+        for (category, usages) in self.categories() {
+            for usage in usages {
+                csv.push_str(&format!(
+                    "{},{},{},{}\n",
+                    category,
+                    escape_csv(&usage.key),
+                    usage.files,
+                    usage.size
+                ));
+            }
+        }
+        csv
+    }
+
+    pub fn duplicates_csv(&self) -> String {
+        let mut csv = String::from("hash,size,path\n");
+        for duplicate in &self.duplicates {
+            for path in &duplicate.paths {
+                csv.push_str(&format!(
+                    "{},{},{}\n",
+                    escape_csv(&duplicate.hash),
+                    duplicate.size,
+                    escape_csv(path)
+                ));
+            }
+        }
+        csv
+    }
+
+    pub fn expiring_csv(&self) -> String {
+        let mut csv = String::from("expires_at,size,path\n");
+        for file in &self.expiring {
+            csv.push_str(&format!(
+                "{},{},{}\n",
+                escape_csv(&file.expires_at),
+                file.size,
+                escape_csv(&file.path)
+            ));
+        }
+        csv
+    }
+
+    pub fn to_html(&self) -> String {
+        let mut html = format!(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Storage report</title>\n<style>\nbody {{ font-family: sans-serif; }}\ntable {{ border-collapse: collapse; margin-bottom: 1.5em; }}\nth, td {{ border: 1px solid #ccc; padding: 0.3em 0.6em; text-align: left; }}\ntd.number {{ text-align: right; }}\n</style>\n</head>\n<body>\n<h1>Storage report</h1>\n<p>{} files, {}, {} in duplicates</p>\n",
+            self.files,
+            format_size(self.size),
+            format_size(self.wasted())
+        );
+        for (category, usages) in self.categories() {
+            html.push_str(&format!(
+                "<h2>By {}</h2>\n<table>\n<tr><th>{}</th><th>Files</th><th>Size</th></tr>\n",
+                category, category
+            ));
+            for usage in usages {
+                html.push_str(&format!(
+                    "<tr><td>{}</td><td class=\"number\">{}</td><td class=\"number\">{}</td></tr>\n",
+                    escape_html(&usage.key),
+                    usage.files,
+                    format_size(usage.size)
+                ));
+            }
+            html.push_str("</table>\n");
+        }
+        html.push_str(
+            "<h2>Duplicates</h2>\n<table>\n<tr><th>Paths</th><th>Size</th><th>Wasted</th></tr>\n",
+        );
+        for duplicate in &self.duplicates {
+            html.push_str(&format!(
+                "<tr><td>{}</td><td class=\"number\">{}</td><td class=\"number\">{}</td></tr>\n",
+                duplicate
+                    .paths
+                    .iter()
+                    .map(|path| escape_html(path))
+                    .collect::<Vec<_>>()
+                    .join("<br>"),
+                format_size(duplicate.size),
+                format_size(duplicate.wasted())
+            ));
+        }
+        html.push_str("</table>\n<h2>Expiring files</h2>\n<table>\n<tr><th>Path</th><th>Size</th><th>Expires at</th></tr>\n");
+        for file in &self.expiring {
+            html.push_str(&format!(
+                "<tr><td>{}</td><td class=\"number\">{}</td><td>{}</td></tr>\n",
+                escape_html(&file.path),
+                format_size(file.size),
+                escape_html(&file.expires_at)
+            ));
+        }
+        html.push_str("</table>\n</body>\n</html>\n");
+        html
+    }
+
+    fn categories(&self) -> [(&str, &Vec<Usage>); 5] {
+        [
+            ("cloud", &self.clouds),
+            ("folder", &self.folders),
+            ("uploader", &self.uploaders),
+            ("type", &self.kinds),
+            ("age", &self.ages),
+        ]
+    }
+}
+
+fn collect<'a>(
+    nodes: impl Iterator<Item = &'a TreeNode>,
+    cloud: &str,
+    folder: &str,
+    files: &mut Vec<(String, String, &'a File)>,
+) {
+    for node in nodes {
+        if node.file.is_folder() {
+            collect(
+                node.children.iter().flatten(),
+                cloud,
+                &format!("{}/{}", folder, node.file.name),
+                files,
+            );
+        } else {
+            files.push((cloud.to_owned(), folder.to_owned(), &node.file));
+        }
+    }
+}
+
+fn is_expired(expires_at: &str, today: &Date) -> bool {
+    expires_at
+        .get(..10)
+        .and_then(|expires_at| expires_at.parse::<Date>().ok())
+        .is_some_and(|expires_at| expires_at < *today)
+}
+
+fn add(usages: &mut BTreeMap<String, Usage>, key: String, file: &File) {
+    let usage = usages.entry(key.clone()).or_insert_with(|| Usage {
+        key,
+        ..Usage::default()
+    });
+    usage.files += 1;
+    usage.size += file.size;
+}
+
+fn sorted(usages: BTreeMap<String, Usage>) -> Vec<Usage> {
+    let mut usages: Vec<Usage> = usages.into_values().collect();
+    usages.sort_by_key(|usage| std::cmp::Reverse(usage.size));
+    usages
+}
+
+fn uploader(file: &File) -> String {
+    file.meta
+        .username
+        .clone()
+        .or_else(|| file.user_id.map(|id| id.to_string()))
+        .unwrap_or_else(|| "unknown".to_owned())
+}
+
+fn age(file: &File, today: &Date) -> &'static str {
+    let days = file
+        .created_at
+        .get(..10)
+        .and_then(|created_at| created_at.parse::<Date>().ok())
+        .map_or(0, |created_at| created_at.days_until(today).max(0) as u64);
+    AGE_BUCKETS
+        .iter()
+        .find(|(limit, _)| days < *limit)
+        .map_or("> 1 year", |(_, label)| label)
+}
+
+fn format_size(size: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KB", "MB", "GB", "TB"];
+    let mut value = size as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} B", size)
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::files::tests::file;
+
+    #[test]
+    fn test_storage_report() {
+        let mut worksheet = file("Woche 3.pdf", "file");
+        worksheet.size = 300;
+        worksheet.hash = Some("abc".to_owned());
+        worksheet.mime_type = Some("application/pdf".to_owned());
+        let mut copy = worksheet.clone();
+        copy.created_at = "2022-01-10 08:00:00".to_owned();
+        copy.expires_at = Some("2023-06-01 00:00:00".to_owned());
+        let mut expired = file("Woche 1.pdf", "file");
+        expired.size = 100;
+        expired.expires_at = Some("2023-05-19 00:00:00".to_owned());
+        let tree = CloudTree {
+            cloud: worksheet.cloud.clone(),
+            children: Some(vec![
+                TreeNode {
+                    file: file("Mathe", "folder"),
+                    children: Some(vec![TreeNode::new(worksheet)]),
+                },
+                TreeNode::new(copy),
+                TreeNode::new(expired),
+            ]),
+        };
+        let report = StorageReport::from_trees(
+            &[("Klasse 5/6".to_owned(), tree)],
+            &Date::new(20, 5, 2023).unwrap(),
+        );
+
+        assert_eq!((report.files, report.size, report.wasted()), (3, 700, 300));
+        assert_eq!(report.folders[0].size, 400);
+        assert_eq!(report.kinds[0].key, "document");
+        assert_eq!(
+            report
+                .ages
+                .iter()
+                .map(|age| age.key.as_str())
+                .collect::<Vec<_>>(),
+            ["< 30 days", "> 1 year"]
+        );
+        assert_eq!(
+            report.duplicates[0].paths,
+            ["/Klasse 5/6/Mathe/Woche 3.pdf", "/Klasse 5/6/Woche 3.pdf"]
+        );
+        assert_eq!(
+            report.expiring_csv(),
+            "expires_at,size,path\n2023-06-01 00:00:00,300,/Klasse 5/6/Woche 3.pdf\n"
+        );
+        assert!(report.usage_csv().contains("cloud,Klasse 5/6,3,700\n"));
+    }
+}
@@ -0,0 +1,253 @@
+use std::cmp::Reverse;
+
+use futures::{stream, StreamExt};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    chat::{Chat, ChatRequest},
+    files::{File, FileKind},
+    prelude::*,
+    timetable::Date,
+};
+
+const CONCURRENT_REQUESTS: usize = 4;
+
+pub struct CloudSearch {
+    token: String,
+    query: String,
+    chats: Option<Vec<Chat>>,
+    extensions: Vec<String>,
+    min_size: Option<u64>,
+    max_size: Option<u64>,
+    uploader: Option<u64>,
+    created_after: Option<Date>,
+}
+
+#[derive(Debug, Clone, Hash, Eq, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct SearchHit {
+    pub score: u32,
+    pub chat: Chat,
+    pub file: File,
+}
+
+#[derive(Debug)]
+pub struct SearchResults {
+    pub hits: Vec<SearchHit>,
+    pub errors: Vec<(Chat, SduiError)>,
+}
+
+impl CloudSearch {
+    pub fn new(token: &str, query: &str) -> Self {
+        CloudSearch {
+            token: token.to_owned(),
+            query: query.to_owned(),
+            chats: None,
+            extensions: vec![],
+            min_size: None,
+            max_size: None,
+            uploader: None,
+            created_after: None,
+        }
+    }
+
+    pub fn chats(mut self, chats: Vec<Chat>) -> Self {
+        self.chats = Some(chats);
+        self
+    }
+
+    pub fn extension(mut self, extension: &str) -> Self {
+        self.extensions
+            .push(extension.trim_start_matches('.').to_ascii_lowercase());
+        self
+    }
+
+    pub fn min_size(mut self, min_size: u64) -> Self {
+        self.min_size = Some(min_size);
+        self
+    }
+
+    pub fn max_size(mut self, max_size: u64) -> Self {
+        self.max_size = Some(max_size);
+        self
+    }
+
+    pub fn uploader(mut self, user_id: u64) -> Self {
+        self.uploader = Some(user_id);
+        self
+    }
+
+    pub fn created_after(mut self, date: &Date) -> Self {
+        self.created_after = Some(*date);
+        self
+    }
+
+    pub async fn request(&self) -> SduiResult<SearchResults> {
+        let (chats, mut rate_limit) = match &self.chats {
+            Some(chats) => (chats.clone(), None),
+            None => {
+                let (chats, rate_limit) = ChatRequest::new(&self.token)
+                    .limit(50)
+                    .request_all()
+                    .await?;
+                (chats, Some(rate_limit))
+            }
+        };
+        let results: Vec<_> = stream::iter(chats)
+            .map(|chat| async move {
+                let result = send::<Vec<File>>(
+                    CLIENT
+                        .get(format!(
+                            "https://api.sdui.app/v1/users/self/channels/cloud/{}/files",
+                            chat.cloud_id
+                        ))
+                        .query(&[("search", &self.query)])
+                        .bearer_auth(&self.token),
+                )
+                .await;
+                (chat, result)
+            })
+            .buffer_unordered(CONCURRENT_REQUESTS)
+            .collect()
+            .await;
+        let mut hits = vec![];
+        let mut errors = vec![];
+        for (chat, result) in results {
+            let (files, cloud_rate_limit) = match result {
+                Ok(result) => result,
+                Err(err) => {
+                    errors.push((chat, err));
+                    continue;
+                }
+            };
+            rate_limit = Some(match rate_limit {
+                Some(rate_limit) => rate_limit.join(cloud_rate_limit),
+                None => cloud_rate_limit,
+            });
+            hits.extend(
+                files
+                    .into_iter()
+                    .filter(|file| self.matches(file))
+                    .map(|file| SearchHit {
+                        score: score(&self.query, &file.name),
+                        chat: chat.clone(),
+                        file,
+                    }),
+            );
+        }
+        Ok((
+            SearchResults {
+                hits: rank(hits),
+                errors,
+            },
+            rate_limit.unwrap_or(RateLimit {
+                limit: 0,
+                remaining: 0,
+            }),
+        ))
+    }
+
+    pub fn matches(&self, file: &File) -> bool {
+        let extension = file
+            .extension
+            .clone()
+            .or_else(|| file.name.rsplit_once('.').map(|(_, ext)| ext.to_owned()))
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        (self.extensions.is_empty() || self.extensions.contains(&extension))
+            && self.min_size.is_none_or(|min_size| file.size >= min_size)
+            && self.max_size.is_none_or(|max_size| file.size <= max_size)
+            && self.uploader.is_none_or(|uploader| {
+                file.user_id == Some(uploader)
+                    || file.user.as_ref().is_some_and(|user| user.id == uploader)
+            })
+            && self.created_after.is_none_or(|date| {
+                file.created_at
+                    .get(..10)
+                    .and_then(|created_at| created_at.parse::<Date>().ok())
+                    .is_some_and(|created_at| created_at >= date)
+            })
+    }
+}
+
+pub fn rank(mut hits: Vec<SearchHit>) -> Vec<SearchHit> {
+    hits.sort_by(|a, b| {
+        (
+            Reverse(a.score),
+            kind_rank(&a.file),
+            Reverse(&a.file.created_at),
+        )
+            .cmp(&(
+                Reverse(b.score),
+                kind_rank(&b.file),
+                Reverse(&b.file.created_at),
+            ))
+    });
+    hits
+}
+
+fn kind_rank(file: &File) -> u8 {
+    match file.kind() {
+        FileKind::DOCUMENT => 0,
+        FileKind::IMAGE | FileKind::VIDEO | FileKind::AUDIO => 1,
+        FileKind::OTHER => 2,
+        FileKind::FOLDER => 3,
+    }
+}
+
+fn score(query: &str, name: &str) -> u32 {
+    let query = query.trim().to_lowercase();
+    let name = name.to_lowercase();
+    let stem = name
+        .rsplit_once('.')
+        .map_or(name.as_str(), |(stem, _)| stem);
+    if query.is_empty() {
+        0
+    } else if stem == query || name == query {
+        100
+    } else if name.starts_with(&query) {
+        75
+    } else if name
+        .split(|c: char| !c.is_alphanumeric())
+        .any(|word| word.starts_with(&query))
+    {
+        60
+    } else if name.contains(&query) {
+        50
+    } else if query.split_whitespace().all(|word| name.contains(word)) {
+        30
+    } else {
+        10
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::files::tests::file;
+
+    #[test]
+    fn test_search_filters() {
+        assert_eq!(score("woche 3", "Woche 3.pdf"), 100);
+        assert_eq!(score("woche", "Woche 3.pdf"), 75);
+        assert_eq!(score("blatt", "Arbeits-Blatt Woche 3.pdf"), 60);
+        assert_eq!(score("3 woche", "Woche 3.pdf"), 30);
+        assert_eq!(score("bruch", "Woche 3.pdf"), 10);
+
+        let mut worksheet = file("Woche 3.pdf", "file");
+        worksheet.size = 300;
+        worksheet.user_id = Some(7);
+        let search = CloudSearch::new("", "woche")
+            .extension(".PDF")
+            .min_size(100)
+            .uploader(7)
+            .created_after(&Date::new(1, 5, 2023).unwrap());
+        assert!(search.matches(&worksheet));
+        assert!(!search.matches(&file("Woche 3.docx", "file")));
+        assert!(!CloudSearch::new("", "woche")
+            .created_after(&Date::new(9, 5, 2023).unwrap())
+            .matches(&worksheet));
+        assert!(!CloudSearch::new("", "woche")
+            .max_size(200)
+            .matches(&worksheet));
+    }
+}
@@ -0,0 +1,306 @@
+use std::{
+    collections::{BTreeMap, HashSet},
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    cloud::Cloud,
+    files::{DownloadRequest, File},
+    prelude::*,
+};
+
+const MANIFEST: &str = ".sdui-manifest.json";
+
+pub struct CloudSync {
+    token: String,
+    cloud: Cloud,
+    root: Option<File>,
+    dir: PathBuf,
+    delete: bool,
+}
+
+#[derive(Debug, Clone, Hash, Eq, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    pub uuid: String,
+    pub hash: Option<String>,
+    pub size: u64,
+    pub updated_at: String,
+}
+
+impl From<&File> for ManifestEntry {
+    fn from(file: &File) -> Self {
+        ManifestEntry {
+            uuid: file.uuid.clone(),
+            hash: file.hash.clone(),
+            size: file.size,
+            updated_at: file.updated_at.clone(),
+        }
+    }
+}
+
+impl ManifestEntry {
+    pub fn is_current(&self, file: &File) -> bool {
+        match (&self.hash, &file.hash) {
+            (Some(hash), Some(remote)) => hash == remote && self.size == file.size,
+            _ => self.size == file.size && self.updated_at == file.updated_at,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Hash, Eq, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct SyncManifest {
+    pub cloud_id: u64,
+    pub files: BTreeMap<String, ManifestEntry>,
+}
+
+impl SyncManifest {
+    pub async fn load(dir: impl AsRef<Path>) -> Result<Option<Self>, SduiError> {
+        match tokio::fs::read(dir.as_ref().join(MANIFEST)).await {
+            Ok(data) => serde_json::from_slice(&data)
+                .map(Some)
+                .map_err(|_| SduiError::JSONError),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(SduiError::IOError(err)),
+        }
+    }
+
+    async fn save(&self, dir: &Path) -> Result<(), SduiError> {
+        let data = serde_json::to_vec_pretty(self).map_err(|_| SduiError::JSONError)?;
+        let temp = dir.join(format!("{}.tmp", MANIFEST));
+        tokio::fs::write(&temp, data)
+            .await
+            .map_err(SduiError::IOError)?;
+        tokio::fs::rename(temp, dir.join(MANIFEST))
+            .await
+            .map_err(SduiError::IOError)
+    }
+}
+
+#[derive(Debug, Clone, Default, Hash, Eq, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct SyncReport {
+    pub downloaded: Vec<String>,
+    pub unchanged: Vec<String>,
+    pub deleted: Vec<String>,
+    pub failed: BTreeMap<String, String>,
+    pub folders: usize,
+}
+
+impl CloudSync {
+    pub fn new(token: &str, cloud: &Cloud, dir: impl Into<PathBuf>) -> Self {
+        CloudSync {
+            token: token.to_owned(),
+            cloud: cloud.clone(),
+            root: None,
+            dir: dir.into(),
+            delete: false,
+        }
+    }
+
+    pub fn root(mut self, root: File) -> Self {
+        self.root = Some(root);
+        self
+    }
+
+    pub fn delete(mut self, delete: bool) -> Self {
+        self.delete = delete;
+        self
+    }
+
+    pub async fn sync(&self) -> SduiResult<SyncReport> {
+        tokio::fs::create_dir_all(&self.dir)
+            .await
+            .map_err(SduiError::IOError)?;
+        let mut manifest = SyncManifest::load(&self.dir)
+            .await?
+            .filter(|manifest| manifest.cloud_id == self.cloud.id)
+            .unwrap_or_else(|| SyncManifest {
+                cloud_id: self.cloud.id,
+                files: BTreeMap::new(),
+            });
+        let mut report = SyncReport::default();
+        let (files, rate_limit) = self.walk(&mut report).await?;
+        for (path, file) in &files {
+            let local = self.dir.join(path);
+            if file.is_folder() {
+                match tokio::fs::create_dir_all(&local).await {
+                    Ok(()) => report.folders += 1,
+                    Err(err) => {
+                        report
+                            .failed
+                            .insert(path.clone(), format!("{:?}", SduiError::IOError(err)));
+                    }
+                }
+                continue;
+            }
+            let exists = tokio::fs::metadata(&local)
+                .await
+                .is_ok_and(|metadata| metadata.len() == file.size);
+            if exists
+                && manifest
+                    .files
+                    .get(path)
+                    .is_some_and(|entry| entry.is_current(file))
+            {
+                report.unchanged.push(path.clone());
+                continue;
+            }
+            if let Err(err) = DownloadRequest::new(file)
+                .token(&self.token)
+                .to_path(&local)
+                .await
+            {
+                report.failed.insert(path.clone(), format!("{:?}", err));
+                continue;
+            }
+            manifest
+                .files
+                .insert(path.clone(), ManifestEntry::from(file));
+            manifest.save(&self.dir).await?;
+            report.downloaded.push(path.clone());
+        }
+        if self.delete {
+            let removed: Vec<String> = manifest
+                .files
+                .keys()
+                .filter(|path| !files.contains_key(*path) && !report.is_failed(path))
+                .cloned()
+                .collect();
+            for path in removed {
+                match tokio::fs::remove_file(self.dir.join(&path)).await {
+                    Err(err) if err.kind() != std::io::ErrorKind::NotFound => {
+                        return Err(SduiError::IOError(err))
+                    }
+                    _ => {}
+                }
+                manifest.files.remove(&path);
+                report.deleted.push(path);
+            }
+        }
+        manifest.save(&self.dir).await?;
+        Ok((report, rate_limit))
+    }
+
+    async fn walk(&self, report: &mut SyncReport) -> SduiResult<BTreeMap<String, File>> {
+        let (entries, mut rate_limit) = match &self.root {
+            Some(root) => root.content(&self.token).await?,
+            None => self.cloud.content(&self.token).await?,
+        };
+        let mut used = HashSet::from([MANIFEST.to_lowercase()]);
+        let mut pending: Vec<(String, File)> = entries
+            .into_iter()
+            .map(|file| (unique_name(&local_name(&file.name), &mut used), file))
+            .collect();
+        let mut files = BTreeMap::new();
+        while let Some((path, file)) = pending.pop() {
+            if file.is_folder() {
+                match file.content(&self.token).await {
+                    Ok((entries, folder_rate_limit)) => {
+                        rate_limit = rate_limit.join(folder_rate_limit);
+                        pending.extend(entries.into_iter().map(|entry| {
+                            let path = format!("{}/{}", path, local_name(&entry.name));
+                            (unique_name(&path, &mut used), entry)
+                        }));
+                    }
+                    Err(err) => {
+                        report.failed.insert(path.clone(), format!("{:?}", err));
+                    }
+                }
+            }
+            files.insert(path, file);
+        }
+        Ok((files, rate_limit))
+    }
+}
+
+impl SyncReport {
+    fn is_failed(&self, path: &str) -> bool {
+        self.failed.keys().any(|failed| {
+            path == failed
+                || path
+                    .strip_prefix(failed.as_str())
+                    .is_some_and(|rest| rest.starts_with('/'))
+        })
+    }
+}
+
+pub(crate) fn unique_name(path: &str, used: &mut HashSet<String>) -> String {
+    let (stem, extension) = match path.rsplit_once('.') {
+        Some((stem, extension))
+            if !stem.is_empty() && !stem.ends_with('/') && !extension.contains('/') =>
+        {
+            (stem, format!(".{}", extension))
+        }
+        _ => (path, String::new()),
+    };
+    let mut unique = path.to_owned();
+    let mut counter = 2;
+    while !used.insert(unique.to_lowercase()) {
+        unique = format!("{} ({}){}", stem, counter, extension);
+        counter += 1;
+    }
+    unique
+}
+
+pub(crate) fn local_name(name: &str) -> String {
+    let name: String = name
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect();
+    match name.trim() {
+        "" | "." | ".." => "_".to_owned(),
+        _ => name,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::files::tests::file;
+
+    #[test]
+    fn test_manifest_entry() {
+        let mut remote = file("document", "file");
+        remote.size = 512;
+        let entry = ManifestEntry::from(&remote);
+        assert!(entry.is_current(&remote));
+
+        remote.updated_at = "2023-05-09 08:00:00".to_owned();
+        assert!(!entry.is_current(&remote));
+
+        remote.hash = Some("abc".to_owned());
+        let entry = ManifestEntry::from(&remote);
+        remote.updated_at = "2023-05-10 08:00:00".to_owned();
+        assert!(entry.is_current(&remote));
+        remote.hash = Some("def".to_owned());
+        assert!(!entry.is_current(&remote));
+
+        assert_eq!(local_name("Woche 3/4: Brüche"), "Woche 3_4_ Brüche");
+        assert_eq!(local_name(".."), "_");
+
+        let mut used = HashSet::new();
+        assert_eq!(unique_name("Mathe/Blatt.pdf", &mut used), "Mathe/Blatt.pdf");
+        assert_eq!(
+            unique_name("Mathe/Blatt.pdf", &mut used),
+            "Mathe/Blatt (2).pdf"
+        );
+        assert_eq!(unique_name("Mathe/.notes", &mut used), "Mathe/.notes");
+        assert_eq!(unique_name("Mathe/.notes", &mut used), "Mathe/.notes (2)");
+        assert_eq!(
+            unique_name("mathe/blatt.pdf", &mut used),
+            "mathe/blatt (3).pdf"
+        );
+
+        let mut report = SyncReport::default();
+        report
+            .failed
+            .insert("Mathe".to_owned(), "Expired".to_owned());
+        assert!(report.is_failed("Mathe/Blatt.pdf"));
+        assert!(!report.is_failed("Mathematik/Blatt.pdf"));
+    }
+}
@@ -0,0 +1,306 @@
+use futures::{future::BoxFuture, FutureExt};
+use serde::{Deserialize, Serialize};
+
+use crate::{cloud::Cloud, files::File, prelude::*};
+
+#[derive(Debug, Clone, Hash, Eq, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct CloudTree {
+    pub cloud: Cloud,
+    pub children: Option<Vec<TreeNode>>,
+}
+
+#[derive(Debug, Clone, Hash, Eq, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct TreeNode {
+    pub file: File,
+    pub children: Option<Vec<TreeNode>>,
+}
+
+impl TreeNode {
+    pub fn new(file: File) -> Self {
+        TreeNode {
+            file,
+            children: None,
+        }
+    }
+
+    pub fn is_loaded(&self) -> bool {
+        !self.file.is_folder() || self.children.is_some()
+    }
+
+    pub fn size(&self) -> u64 {
+        match &self.children {
+            Some(children) => children.iter().map(TreeNode::size).sum(),
+            None => self.file.size,
+        }
+    }
+
+    pub fn file_count(&self) -> u64 {
+        match &self.children {
+            Some(children) => children.iter().map(TreeNode::file_count).sum(),
+            None if self.file.is_folder() => self.file.meta.files_count,
+            None => 1,
+        }
+    }
+
+    async fn load(
+        &mut self,
+        token: &str,
+        rate_limit: &mut Option<RateLimit>,
+    ) -> Result<&mut Vec<TreeNode>, SduiError> {
+        if self.children.is_none() {
+            let (files, content_rate_limit) = self.file.content(token).await?;
+            join_rate_limit(rate_limit, content_rate_limit);
+            self.children = Some(files.into_iter().map(TreeNode::new).collect());
+        }
+        Ok(self.children.get_or_insert_with(Vec::new))
+    }
+}
+
+impl CloudTree {
+    pub fn new(cloud: &Cloud) -> Self {
+        CloudTree {
+            cloud: cloud.clone(),
+            children: None,
+        }
+    }
+
+    pub async fn load(&mut self, token: &str) -> SduiResult<&mut Vec<TreeNode>> {
+        let mut rate_limit = None;
+        let children = self.load_children(token, &mut rate_limit).await?;
+        Ok((children, rate_limit.unwrap_or_else(RateLimit::unknown)))
+    }
+
+    pub async fn load_all(&mut self, token: &str) -> SduiResult<()> {
+        let mut rate_limit = None;
+        let children = self.load_children(token, &mut rate_limit).await?;
+        load_all(children, token, &mut rate_limit).await?;
+        Ok(((), rate_limit.unwrap_or_else(RateLimit::unknown)))
+    }
+
+    pub async fn resolve(&mut self, token: &str, path: &str) -> SduiResult<Option<File>> {
+        let mut rate_limit = None;
+        let segments = segments(path);
+        let file = match segments.split_last() {
+            Some((name, folders)) => self
+                .children_mut(token, folders, &mut rate_limit)
+                .await?
+                .and_then(|nodes| nodes.iter().find(|node| node.file.name == *name))
+                .map(|node| node.file.clone()),
+            None => None,
+        };
+        Ok((file, rate_limit.unwrap_or_else(RateLimit::unknown)))
+    }
+
+    pub async fn list(&mut self, token: &str, path: &str) -> SduiResult<Option<Vec<File>>> {
+        let mut rate_limit = None;
+        let files = self
+            .children_mut(token, &segments(path), &mut rate_limit)
+            .await?
+            .map(|nodes| nodes.iter().map(|node| node.file.clone()).collect());
+        Ok((files, rate_limit.unwrap_or_else(RateLimit::unknown)))
+    }
+
+    pub fn invalidate(&mut self) {
+        self.children = None;
+    }
+
+    async fn load_children(
+        &mut self,
+        token: &str,
+        rate_limit: &mut Option<RateLimit>,
+    ) -> Result<&mut Vec<TreeNode>, SduiError> {
+        if self.children.is_none() {
+            let (files, content_rate_limit) = self.cloud.content(token).await?;
+            join_rate_limit(rate_limit, content_rate_limit);
+            self.children = Some(files.into_iter().map(TreeNode::new).collect());
+        }
+        Ok(self.children.get_or_insert_with(Vec::new))
+    }
+
+    async fn children_mut(
+        &mut self,
+        token: &str,
+        folders: &[&str],
+        rate_limit: &mut Option<RateLimit>,
+    ) -> Result<Option<&mut Vec<TreeNode>>, SduiError> {
+        let mut nodes = self.load_children(token, rate_limit).await?;
+        for folder in folders {
+            match nodes
+                .iter_mut()
+                .find(|node| node.file.name == *folder && node.file.is_folder())
+            {
+                Some(node) => nodes = node.load(token, rate_limit).await?,
+                None => return Ok(None),
+            }
+        }
+        Ok(Some(nodes))
+    }
+
+    pub fn get(&self, path: &str) -> Option<&TreeNode> {
+        let mut nodes = self.children.as_ref()?;
+        let segments = segments(path);
+        let (name, folders) = segments.split_last()?;
+        for folder in folders {
+            nodes = nodes
+                .iter()
+                .find(|node| node.file.name == *folder)?
+                .children
+                .as_ref()?;
+        }
+        nodes.iter().find(|node| node.file.name == *name)
+    }
+
+    pub fn glob(&self, pattern: &str) -> Vec<(String, &File)> {
+        let pattern = segments(pattern);
+        let mut matches = vec![];
+        let mut pending: Vec<(String, &TreeNode)> = self
+            .children
+            .iter()
+            .flatten()
+            .map(|node| (format!("/{}", node.file.name), node))
+            .collect();
+        while let Some((path, node)) = pending.pop() {
+            if glob_match(&pattern, &segments(&path)) {
+                matches.push((path.clone(), &node.file));
+            }
+            pending.extend(
+                node.children
+                    .iter()
+                    .flatten()
+                    .map(|child| (format!("{}/{}", path, child.file.name), child)),
+            );
+        }
+        matches.sort_by(|a, b| a.0.cmp(&b.0));
+        matches
+    }
+
+    pub fn size(&self) -> u64 {
+        self.children.iter().flatten().map(TreeNode::size).sum()
+    }
+
+    pub fn file_count(&self) -> u64 {
+        self.children
+            .iter()
+            .flatten()
+            .map(TreeNode::file_count)
+            .sum()
+    }
+}
+
+fn load_all<'a>(
+    nodes: &'a mut [TreeNode],
+    token: &'a str,
+    rate_limit: &'a mut Option<RateLimit>,
+) -> BoxFuture<'a, Result<(), SduiError>> {
+    async move {
+        for node in nodes.iter_mut().filter(|node| node.file.is_folder()) {
+            let children = node.load(token, rate_limit).await?;
+            load_all(children, token, rate_limit).await?;
+        }
+        Ok(())
+    }
+    .boxed()
+}
+
+fn segments(path: &str) -> Vec<&str> {
+    path.split('/')
+        .filter(|segment| !segment.is_empty())
+        .collect()
+}
+
+fn glob_match(pattern: &[&str], path: &[&str]) -> bool {
+    match pattern.split_first() {
+        None => path.is_empty(),
+        Some((&"**", rest)) => (0..=path.len()).any(|skip| glob_match(rest, &path[skip..])),
+        Some((segment, rest)) => match path.split_first() {
+            Some((name, path)) => segment_match(segment, name) && glob_match(rest, path),
+            None => false,
+        },
+    }
+}
+
+fn segment_match(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    let (mut p, mut n) = (0, 0);
+    let mut backtrack = None;
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, n));
+                p += 1;
+            }
+            Some(c) if *c == '?' || *c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match backtrack {
+                Some((star, matched)) => {
+                    p = star + 1;
+                    n = matched + 1;
+                    backtrack = Some((star, matched + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::files::tests::file;
+
+    #[test]
+    fn test_cloud_tree() {
+        let mut worksheet = file("Woche 3.pdf", "file");
+        worksheet.size = 300;
+        let mut notes = file("Notizen.txt", "file");
+        notes.size = 50;
+        let mut unloaded = file("Archiv", "folder");
+        unloaded.meta.files_count = 4;
+        let tree = CloudTree {
+            cloud: worksheet.cloud.clone(),
+            children: Some(vec![
+                TreeNode {
+                    file: file("Mathe", "folder"),
+                    children: Some(vec![
+                        TreeNode {
+                            file: file("Arbeitsblätter", "folder"),
+                            children: Some(vec![TreeNode::new(worksheet)]),
+                        },
+                        TreeNode::new(notes),
+                    ]),
+                },
+                TreeNode::new(unloaded),
+            ]),
+        };
+
+        assert_eq!(
+            tree.get("/Mathe/Arbeitsblätter/Woche 3.pdf")
+                .map(|node| node.file.size),
+            Some(300)
+        );
+        assert!(tree.get("/Mathe/Woche 3.pdf").is_none());
+        assert_eq!(tree.get("/Mathe").unwrap().size(), 350);
+        assert_eq!(tree.file_count(), 6);
+        assert!(!tree.get("/Archiv").unwrap().is_loaded());
+
+        let paths = |pattern| {
+            tree.glob(pattern)
+                .into_iter()
+                .map(|(path, _)| path)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            paths("/Mathe/*"),
+            ["/Mathe/Arbeitsblätter", "/Mathe/Notizen.txt"]
+        );
+        assert_eq!(paths("**/*.pdf"), ["/Mathe/Arbeitsblätter/Woche 3.pdf"]);
+        assert_eq!(paths("/Mathe/**/Woche ?.pdf").len(), 1);
+
+        let json = serde_json::to_string(&tree).unwrap();
+        assert_eq!(serde_json::from_str::<CloudTree>(&json).unwrap(), tree);
+    }
+}
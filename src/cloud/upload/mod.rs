@@ -0,0 +1,190 @@
+use std::{
+    path::Path,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+};
+
+use bytes::Bytes;
+use futures::{stream, Stream, StreamExt};
+use reqwest::{
+    multipart::{Form, Part},
+    Body,
+};
+use tokio::io::AsyncRead;
+use tokio_util::io::ReaderStream;
+
+use crate::{cloud::Cloud, files::File, prelude::*};
+
+const CHUNK_SIZE: usize = 64 * 1024;
+
+type Progress = Arc<dyn Fn(u64, u64) + Send + Sync>;
+
+pub struct UploadRequest {
+    token: String,
+    cloud: Cloud,
+    parent: Option<File>,
+    progress: Option<Progress>,
+}
+
+impl UploadRequest {
+    pub fn new(token: &str, cloud: &Cloud) -> Self {
+        UploadRequest {
+            token: token.to_owned(),
+            cloud: cloud.clone(),
+            parent: None,
+            progress: None,
+        }
+    }
+
+    pub fn parent(mut self, parent: File) -> Self {
+        self.parent = Some(parent);
+        self
+    }
+
+    pub fn progress(mut self, progress: impl Fn(u64, u64) + Send + Sync + 'static) -> Self {
+        self.progress = Some(Arc::new(progress));
+        self
+    }
+
+    pub fn validate(&self, name: &str, size: u64) -> Result<(), SduiError> {
+        self.cloud.validate_upload(name, size)
+    }
+
+    pub async fn upload_bytes(&self, name: &str, data: impl Into<Bytes>) -> SduiResult<File> {
+        let data: Bytes = data.into();
+        let size = data.len() as u64;
+        let chunks: Vec<Result<Bytes, std::io::Error>> = (0..data.len())
+            .step_by(CHUNK_SIZE)
+            .map(|start| Ok(data.slice(start..(start + CHUNK_SIZE).min(data.len()))))
+            .collect();
+        self.upload_stream(name, stream::iter(chunks), size).await
+    }
+
+    pub async fn upload_reader<R>(&self, name: &str, reader: R, size: u64) -> SduiResult<File>
+    where
+        R: AsyncRead + Send + Sync + 'static,
+    {
+        self.upload_stream(name, ReaderStream::with_capacity(reader, CHUNK_SIZE), size)
+            .await
+    }
+
+    pub async fn upload_path(&self, path: impl AsRef<Path>) -> SduiResult<File> {
+        let path = path.as_ref();
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let file = tokio::fs::File::open(path)
+            .await
+            .map_err(SduiError::IOError)?;
+        let size = file.metadata().await.map_err(SduiError::IOError)?.len();
+        self.upload_reader(&name, file, size).await
+    }
+
+    async fn upload_stream<S>(&self, name: &str, data: S, size: u64) -> SduiResult<File>
+    where
+        S: Stream<Item = Result<Bytes, std::io::Error>> + Send + Sync + 'static,
+    {
+        self.validate(name, size)?;
+        let sent = AtomicU64::new(0);
+        let progress = self.progress.clone();
+        let data = data.inspect(move |chunk| {
+            if let (Some(progress), Ok(chunk)) = (&progress, chunk) {
+                let sent =
+                    sent.fetch_add(chunk.len() as u64, Ordering::Relaxed) + chunk.len() as u64;
+                progress(sent, size);
+            }
+        });
+        let mut form = Form::new().part(
+            "file",
+            Part::stream_with_length(Body::wrap_stream(data), size).file_name(name.to_owned()),
+        );
+        if let Some(parent) = &self.parent {
+            form = form.text("parent_id", parent.uuid.clone());
+        }
+        send(
+            CLIENT
+                .post(self.cloud.upload_uri())
+                .bearer_auth(&self.token)
+                .multipart(form),
+        )
+        .await
+    }
+}
+
+impl Cloud {
+    pub fn upload_uri(&self) -> String {
+        self.meta.upload.clone().unwrap_or_else(|| {
+            format!(
+                "https://api.sdui.app/v1/users/self/channels/cloud/{}/files",
+                self.id
+            )
+        })
+    }
+
+    pub fn validate_upload(&self, name: &str, size: u64) -> Result<(), SduiError> {
+        if self.can.upload == 0 {
+            return Err(SduiError::PermissionDenied);
+        }
+        if let Some((_, extension)) = name.rsplit_once('.') {
+            let forbidden = self.meta.forbidden.iter().any(|forbidden| {
+                forbidden
+                    .trim_start_matches('.')
+                    .eq_ignore_ascii_case(extension)
+            });
+            if forbidden {
+                return Err(SduiError::ForbiddenExtension(extension.to_owned()));
+            }
+        }
+        match self.meta.upload_limit {
+            Some(limit) if size > limit => Err(SduiError::FileTooLarge { size, limit }),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cloud::{CloudCan, CloudMeta};
+
+    #[test]
+    fn test_validate_upload() {
+        let mut cloud = Cloud {
+            can: CloudCan {
+                upload: 1,
+                create_protected_folder: 0,
+            },
+            disabled_at: None,
+            id: 1,
+            meta: CloudMeta {
+                download: None,
+                forbidden: vec!["exe".to_owned()],
+                max_number: 10,
+                rename: None,
+                upload: None,
+                upload_limit: Some(1024),
+            },
+            updated_at: None,
+        };
+        assert!(cloud.validate_upload("Arbeitsblatt.pdf", 512).is_ok());
+        assert!(matches!(
+            cloud.validate_upload("setup.EXE", 512),
+            Err(SduiError::ForbiddenExtension(_))
+        ));
+        assert!(matches!(
+            cloud.validate_upload("video.mp4", 2048),
+            Err(SduiError::FileTooLarge {
+                size: 2048,
+                limit: 1024
+            })
+        ));
+        cloud.can.upload = 0;
+        assert!(matches!(
+            cloud.validate_upload("Arbeitsblatt.pdf", 512),
+            Err(SduiError::PermissionDenied)
+        ));
+    }
+}
@@ -0,0 +1,530 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use bytes::Bytes;
+use futures::stream::BoxStream;
+use futures::{stream, Stream, StreamExt};
+use md5::Md5;
+use reqwest::{
+    header::{HeaderValue, CONTENT_DISPOSITION, CONTENT_TYPE, RANGE},
+    RequestBuilder, Response, StatusCode,
+};
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
+use tokio::io::{AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+use crate::{files::File, prelude::*};
+
+type Progress = Arc<dyn Fn(u64, u64) + Send + Sync>;
+
+#[derive(Clone)]
+pub struct DownloadRequest {
+    file: File,
+    token: Option<String>,
+    progress: Option<Progress>,
+    verify: bool,
+}
+
+struct Verification {
+    hasher: FileHasher,
+    expected: String,
+}
+
+enum FileHasher {
+    Md5(Md5),
+    Sha1(Sha1),
+    Sha256(Sha256),
+}
+
+impl FileHasher {
+    fn for_hash(hash: &str) -> Option<Self> {
+        if !hash.chars().all(|c| c.is_ascii_hexdigit()) {
+            return None;
+        }
+        match hash.len() {
+            32 => Some(FileHasher::Md5(Md5::new())),
+            40 => Some(FileHasher::Sha1(Sha1::new())),
+            64 => Some(FileHasher::Sha256(Sha256::new())),
+            _ => None,
+        }
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        match self {
+            FileHasher::Md5(hasher) => hasher.update(data),
+            FileHasher::Sha1(hasher) => hasher.update(data),
+            FileHasher::Sha256(hasher) => hasher.update(data),
+        }
+    }
+
+    fn finish(self) -> String {
+        let digest = match self {
+            FileHasher::Md5(hasher) => hasher.finalize().to_vec(),
+            FileHasher::Sha1(hasher) => hasher.finalize().to_vec(),
+            FileHasher::Sha256(hasher) => hasher.finalize().to_vec(),
+        };
+        digest.iter().map(|byte| format!("{:02x}", byte)).collect()
+    }
+}
+
+impl DownloadRequest {
+    pub fn new(file: &File) -> Self {
+        DownloadRequest {
+            file: file.clone(),
+            token: None,
+            progress: None,
+            verify: true,
+        }
+    }
+
+    pub fn token(mut self, token: &str) -> Self {
+        self.token = Some(token.to_owned());
+        self
+    }
+
+    pub fn progress(mut self, progress: impl Fn(u64, u64) + Send + Sync + 'static) -> Self {
+        self.progress = Some(Arc::new(progress));
+        self
+    }
+
+    pub fn verify(mut self, verify: bool) -> Self {
+        self.verify = verify;
+        self
+    }
+
+    pub async fn to_writer<W>(&self, writer: &mut W) -> Result<u64, SduiError>
+    where
+        W: AsyncWrite + Unpin,
+    {
+        self.download(writer, 0, self.verification()).await
+    }
+
+    pub async fn resume_to_writer<W>(&self, writer: &mut W, offset: u64) -> Result<u64, SduiError>
+    where
+        W: AsyncWrite + Unpin,
+    {
+        let verification = if offset == 0 {
+            self.verification()
+        } else {
+            None
+        };
+        self.download(writer, offset, verification).await
+    }
+
+    pub async fn to_path(&self, path: impl AsRef<Path>) -> Result<u64, SduiError> {
+        let path = path.as_ref();
+        let mut partial = path.as_os_str().to_owned();
+        partial.push(".part");
+        let partial = PathBuf::from(partial);
+        let mut file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .read(true)
+            .append(true)
+            .open(&partial)
+            .await
+            .map_err(SduiError::IOError)?;
+        let mut offset = file.metadata().await.map_err(SduiError::IOError)?.len();
+        if offset > self.file.size {
+            file.set_len(0).await.map_err(SduiError::IOError)?;
+            offset = 0;
+        }
+        let mut verification = self.verification();
+        if let Some(Verification { hasher, .. }) = &mut verification {
+            let mut buffer = vec![0; 64 * 1024];
+            loop {
+                let read = file.read(&mut buffer).await.map_err(SduiError::IOError)?;
+                if read == 0 {
+                    break;
+                }
+                hasher.update(&buffer[..read]);
+            }
+        }
+        let written = match self.download(&mut file, offset, verification).await {
+            Err(err @ SduiError::HashMismatch { .. }) => {
+                drop(file);
+                tokio::fs::remove_file(&partial)
+                    .await
+                    .map_err(SduiError::IOError)?;
+                return Err(err);
+            }
+            result => result?,
+        };
+        drop(file);
+        tokio::fs::rename(&partial, path)
+            .await
+            .map_err(SduiError::IOError)?;
+        Ok(written)
+    }
+
+    fn verification(&self) -> Option<Verification> {
+        let hash = self.file.hash.as_deref().filter(|_| self.verify)?;
+        Some(Verification {
+            hasher: FileHasher::for_hash(hash)?,
+            expected: hash.to_ascii_lowercase(),
+        })
+    }
+
+    async fn download<W>(
+        &self,
+        writer: &mut W,
+        offset: u64,
+        verification: Option<Verification>,
+    ) -> Result<u64, SduiError>
+    where
+        W: AsyncWrite + Unpin,
+    {
+        if offset > 0 && offset == self.file.size {
+            return verify(verification).map(|_| offset);
+        }
+        let token = self.token.as_deref();
+        let uris = [
+            self.file.meta.download_uri.as_str(),
+            self.file.meta.uri.as_str(),
+        ];
+        let response = match (send_first(&uris, token, offset).await, token) {
+            (Err(SduiError::Expired), Some(token)) => {
+                let (file, _) = self.file.refresh(token).await?;
+                let uris = [file.meta.download_uri.as_str(), file.meta.uri.as_str()];
+                send_first(&uris, Some(token), offset).await?
+            }
+            (result, _) => result?,
+        };
+        let skip = if response.status() == StatusCode::PARTIAL_CONTENT {
+            0
+        } else {
+            offset
+        };
+        write_stream(
+            response.bytes_stream(),
+            writer,
+            offset,
+            skip,
+            self.file.size,
+            verification,
+            self.progress.as_ref(),
+        )
+        .await
+    }
+}
+
+impl File {
+    pub async fn open(&self, token: &str) -> Result<DownloadStream, SduiError> {
+        let uris = [self.meta.download_uri.as_str(), self.meta.uri.as_str()];
+        match open_first(&uris, token).await {
+            Err(SduiError::Expired) => {
+                let (file, _) = self.refresh(token).await?;
+                open_first(&[&file.meta.download_uri, &file.meta.uri], token).await
+            }
+            result => result,
+        }
+    }
+
+    pub async fn refresh(&self, token: &str) -> SduiResult<File> {
+        request(&self.meta.details_uri, token).await
+    }
+
+    pub async fn download(&self, token: &str) -> Result<Bytes, SduiError> {
+        self.open(token).await?.bytes().await
+    }
+
+    pub async fn download_to<W>(&self, token: &str, writer: &mut W) -> Result<u64, SduiError>
+    where
+        W: AsyncWrite + Unpin,
+    {
+        DownloadRequest::new(self)
+            .token(token)
+            .to_writer(writer)
+            .await
+    }
+}
+
+pub async fn download_all(
+    requests: Vec<(DownloadRequest, PathBuf)>,
+    workers: usize,
+) -> Vec<Result<u64, SduiError>> {
+    stream::iter(requests)
+        .map(|(request, path)| async move { request.to_path(path).await })
+        .buffered(workers.max(1))
+        .collect()
+        .await
+}
+
+pub struct DownloadStream {
+    pub content_type: Option<String>,
+    pub filename: Option<String>,
+    pub content_length: Option<u64>,
+    pub stream: BoxStream<'static, Result<Bytes, SduiError>>,
+}
+
+impl DownloadStream {
+    fn from_response(response: Response) -> Self {
+        let header = |name| {
+            response
+                .headers()
+                .get(name)
+                .and_then(|value: &HeaderValue| value.to_str().ok())
+                .map(str::to_owned)
+        };
+        DownloadStream {
+            content_type: header(CONTENT_TYPE),
+            filename: header(CONTENT_DISPOSITION)
+                .and_then(|value| content_disposition_filename(&value)),
+            content_length: response.content_length(),
+            stream: response
+                .bytes_stream()
+                .map(|chunk| chunk.map_err(SduiError::RequestError))
+                .boxed(),
+        }
+    }
+
+    pub async fn bytes(mut self) -> Result<Bytes, SduiError> {
+        let mut data = Vec::with_capacity(self.content_length.unwrap_or(0) as usize);
+        while let Some(chunk) = self.stream.next().await {
+            data.extend_from_slice(&chunk?);
+        }
+        Ok(data.into())
+    }
+}
+
+pub(crate) fn needs_auth(url: &str) -> bool {
+    let authority = url
+        .split_once("://")
+        .map_or(url, |(_, rest)| rest)
+        .split(['/', '?', '#'])
+        .next()
+        .unwrap_or_default();
+    let host = authority
+        .rsplit('@')
+        .next()
+        .unwrap_or_default()
+        .split(':')
+        .next()
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    ["sdui.app", "sdui.de"]
+        .iter()
+        .any(|domain| host == *domain || host.ends_with(&format!(".{}", domain)))
+}
+
+pub(crate) fn download_request(url: &str, token: Option<&str>) -> (RequestBuilder, bool) {
+    match token.filter(|_| needs_auth(url)) {
+        Some(token) => (CLIENT.get(url).bearer_auth(token), true),
+        None => (CLIENT.get(url), false),
+    }
+}
+
+pub(crate) fn check_download(
+    response: Response,
+    authenticated: bool,
+) -> Result<Response, SduiError> {
+    match response.status() {
+        StatusCode::UNAUTHORIZED => Err(SduiError::NotLoggedIn),
+        StatusCode::FORBIDDEN if authenticated => Err(SduiError::PermissionDenied),
+        StatusCode::FORBIDDEN | StatusCode::GONE => Err(SduiError::Expired),
+        _ => response.error_for_status().map_err(SduiError::RequestError),
+    }
+}
+
+pub async fn open_download(url: &str, token: Option<&str>) -> Result<DownloadStream, SduiError> {
+    send_download(url, token, 0)
+        .await
+        .map(DownloadStream::from_response)
+}
+
+pub(crate) async fn open_first(urls: &[&str], token: &str) -> Result<DownloadStream, SduiError> {
+    send_first(urls, Some(token), 0)
+        .await
+        .map(DownloadStream::from_response)
+}
+
+async fn send_download(url: &str, token: Option<&str>, offset: u64) -> Result<Response, SduiError> {
+    let (mut request, authenticated) = download_request(url, token);
+    if offset > 0 {
+        request = request.header(RANGE, format!("bytes={}-", offset));
+    }
+    let response = request.send().await.map_err(SduiError::RequestError)?;
+    check_download(response, authenticated)
+}
+
+async fn send_first(
+    urls: &[&str],
+    token: Option<&str>,
+    offset: u64,
+) -> Result<Response, SduiError> {
+    let mut error = SduiError::Expired;
+    for url in urls.iter().filter(|url| !url.is_empty()) {
+        match send_download(url, token, offset).await {
+            Err(err @ (SduiError::Expired | SduiError::PermissionDenied)) => error = err,
+            result => return result,
+        }
+    }
+    Err(error)
+}
+
+pub async fn download_url(url: &str, token: Option<&str>) -> Result<Bytes, SduiError> {
+    open_download(url, token).await?.bytes().await
+}
+
+pub(crate) fn content_disposition_filename(value: &str) -> Option<String> {
+    let mut filename = None;
+    for parameter in value.split(';').map(str::trim) {
+        let Some((key, value)) = parameter.split_once('=') else {
+            continue;
+        };
+        match key.trim().to_ascii_lowercase().as_str() {
+            "filename*" => {
+                let encoded = value.trim().rsplit('\'').next().unwrap_or_default();
+                return Some(percent_decode(encoded));
+            }
+            "filename" => filename = Some(value.trim().trim_matches('"').replace("\\\"", "\"")),
+            _ => {}
+        }
+    }
+    filename
+}
+
+pub(crate) fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        let escaped = bytes
+            .get(index + 1..index + 3)
+            .filter(|_| bytes[index] == b'%')
+            .and_then(|hex| u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok());
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                index += 3;
+            }
+            None => {
+                decoded.push(bytes[index]);
+                index += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+async fn write_stream<S, E, W>(
+    data: S,
+    writer: &mut W,
+    offset: u64,
+    mut skip: u64,
+    size: u64,
+    mut verification: Option<Verification>,
+    progress: Option<&Progress>,
+) -> Result<u64, SduiError>
+where
+    S: Stream<Item = Result<Bytes, E>>,
+    E: Into<SduiError>,
+    W: AsyncWrite + Unpin,
+{
+    let mut data = std::pin::pin!(data);
+    let mut written = offset;
+    while let Some(chunk) = data.next().await {
+        let mut chunk = chunk.map_err(Into::into)?;
+        if skip > 0 {
+            let skipped = skip.min(chunk.len() as u64);
+            chunk = chunk.slice(skipped as usize..);
+            skip -= skipped;
+        }
+        if chunk.is_empty() {
+            continue;
+        }
+        writer.write_all(&chunk).await.map_err(SduiError::IOError)?;
+        if let Some(verification) = &mut verification {
+            verification.hasher.update(&chunk);
+        }
+        written += chunk.len() as u64;
+        if let Some(progress) = progress {
+            progress(written, size);
+        }
+    }
+    writer.flush().await.map_err(SduiError::IOError)?;
+    verify(verification).map(|_| written)
+}
+
+fn verify(verification: Option<Verification>) -> Result<(), SduiError> {
+    match verification {
+        Some(Verification { hasher, expected }) => {
+            let actual = hasher.finish();
+            if actual == expected {
+                Ok(())
+            } else {
+                Err(SduiError::HashMismatch { expected, actual })
+            }
+        }
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_write_stream() {
+        let chunks: Vec<Result<Bytes, SduiError>> =
+            vec![Ok(Bytes::from_static(b"ab")), Ok(Bytes::from_static(b"c"))];
+        let mut output = vec![];
+        let verification = Some(Verification {
+            hasher: FileHasher::for_hash("900150983cd24fb0d6963f7d28e17f72").unwrap(),
+            expected: "900150983cd24fb0d6963f7d28e17f72".to_owned(),
+        });
+        let written = write_stream(
+            stream::iter(chunks),
+            &mut output,
+            0,
+            0,
+            3,
+            verification,
+            None,
+        )
+        .await
+        .unwrap();
+        assert_eq!((written, output.as_slice()), (3, b"abc".as_slice()));
+
+        let chunks: Vec<Result<Bytes, SduiError>> = vec![Ok(Bytes::from_static(b"abc"))];
+        let mut output = b"a".to_vec();
+        let mut hasher = FileHasher::for_hash(&"0".repeat(64)).unwrap();
+        hasher.update(b"a");
+        let verification = Some(Verification {
+            hasher,
+            expected: "0".repeat(64),
+        });
+        let result = write_stream(
+            stream::iter(chunks),
+            &mut output,
+            1,
+            1,
+            3,
+            verification,
+            None,
+        )
+        .await;
+        assert_eq!(output, b"abc");
+        assert!(matches!(result, Err(SduiError::HashMismatch { .. })));
+    }
+
+    #[test]
+    fn test_download_helpers() {
+        assert!(needs_auth("https://api.sdui.app/v1/files/1/download"));
+        assert!(!needs_auth(
+            "https://sdui-files.s3.amazonaws.com/a?sig=sdui.app"
+        ));
+        assert_eq!(
+            content_disposition_filename("attachment; filename=\"Woche 3.pdf\"").as_deref(),
+            Some("Woche 3.pdf")
+        );
+        assert_eq!(
+            content_disposition_filename(
+                "attachment; filename=\"Arbeitsbl.pdf\"; filename*=UTF-8''Arbeitsbl%C3%A4tter.pdf"
+            )
+            .as_deref(),
+            Some("Arbeitsblätter.pdf")
+        );
+    }
+}
@@ -0,0 +1,306 @@
+use std::collections::HashSet;
+
+use async_zip::{tokio::write::ZipFileWriter, Compression, ZipDateTimeBuilder, ZipEntryBuilder};
+use futures::{AsyncWriteExt, StreamExt};
+use serde::{Deserialize, Serialize};
+use tokio::io::AsyncWrite;
+
+use crate::{
+    channel::Attachment,
+    cloud::{local_name, unique_name, Cloud},
+    files::{File, FileKind},
+    news::News,
+    prelude::*,
+};
+
+const MANIFEST: &str = "manifest.json";
+
+pub struct ZipExport {
+    token: String,
+    entries: Vec<ExportEntry>,
+    paths: HashSet<String>,
+}
+
+#[derive(Debug, Clone, Hash, Eq, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ExportSource {
+    Folder {
+        uuid: Option<String>,
+        cloud_id: u64,
+    },
+    File {
+        uuid: String,
+        cloud_id: u64,
+        hash: Option<String>,
+    },
+    Attachment {
+        id: u64,
+        uuid: String,
+        source_type: String,
+        source_id: u64,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExportEntry {
+    pub path: String,
+    pub size: u64,
+    pub modified_at: Option<String>,
+    pub source: ExportSource,
+    #[serde(skip)]
+    content: Option<ExportContent>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[allow(clippy::large_enum_variant)]
+enum ExportContent {
+    File(File),
+    Attachment(Attachment),
+}
+
+impl ZipExport {
+    pub fn new(token: &str) -> Self {
+        ZipExport {
+            token: token.to_owned(),
+            entries: vec![],
+            paths: HashSet::from([MANIFEST.to_lowercase()]),
+        }
+    }
+
+    pub fn entries(&self) -> &[ExportEntry] {
+        &self.entries
+    }
+
+    pub async fn folder(
+        mut self,
+        cloud: &Cloud,
+        folder: Option<&File>,
+        prefix: &str,
+    ) -> Result<Self, SduiError> {
+        let prefix = sanitize(prefix);
+        let (files, _) = match folder {
+            Some(folder) => folder.content(&self.token).await?,
+            None => cloud.content(&self.token).await?,
+        };
+        if !prefix.is_empty() {
+            self.push_folder(&prefix, folder.map(|folder| folder.uuid.clone()), cloud.id);
+        }
+        let mut pending: Vec<(String, File)> = files
+            .into_iter()
+            .rev()
+            .map(|file| (prefix.clone(), file))
+            .collect();
+        while let Some((parent, file)) = pending.pop() {
+            let path = join(&parent, &file.name);
+            if file.is_folder() {
+                let path = self.push_folder(&path, Some(file.uuid.clone()), file.cloud_id);
+                let (children, _) = file.content(&self.token).await?;
+                pending.extend(
+                    children
+                        .into_iter()
+                        .rev()
+                        .map(|child| (path.clone(), child)),
+                );
+            } else {
+                self.push_file(&path, file);
+            }
+        }
+        Ok(self)
+    }
+
+    pub fn file(mut self, folder: &str, file: &File) -> Self {
+        self.push_file(&join(folder, &file.name), file.clone());
+        self
+    }
+
+    pub fn attachments(mut self, folder: &str, attachments: &[Attachment]) -> Self {
+        for attachment in attachments {
+            let path = self.unique(&join(folder, &attachment.name));
+            self.entries.push(ExportEntry {
+                path,
+                size: attachment.size,
+                modified_at: Some(
+                    attachment
+                        .updated_at
+                        .clone()
+                        .unwrap_or_else(|| attachment.created_at.clone()),
+                ),
+                source: ExportSource::Attachment {
+                    id: attachment.id,
+                    uuid: attachment.uuid.clone(),
+                    source_type: attachment.source_type.clone(),
+                    source_id: attachment.source_id,
+                },
+                content: Some(ExportContent::Attachment(attachment.clone())),
+            });
+        }
+        self
+    }
+
+    pub fn news(self, news: &[News]) -> Self {
+        news.iter().fold(self, |export, news| {
+            let folder = local_name(&format!("{} - {}", news.id, news.title));
+            export.attachments(&folder, &news.attachments)
+        })
+    }
+
+    pub fn manifest(&self) -> Result<String, SduiError> {
+        serde_json::to_string_pretty(&self.entries).map_err(|_| SduiError::JSONError)
+    }
+
+    pub async fn write<W>(&self, writer: W) -> Result<W, SduiError>
+    where
+        W: AsyncWrite + Unpin,
+    {
+        let mut zip = ZipFileWriter::with_tokio(writer);
+        zip.write_entry_whole(
+            ZipEntryBuilder::new(MANIFEST.into(), Compression::Deflate),
+            self.manifest()?.as_bytes(),
+        )
+        .await
+        .map_err(zip_error)?;
+        for entry in &self.entries {
+            let (mut download, kind) = match &entry.content {
+                None => {
+                    zip.write_entry_whole(
+                        builder(&format!("{}/", entry.path), entry, Compression::Stored),
+                        &[],
+                    )
+                    .await
+                    .map_err(zip_error)?;
+                    continue;
+                }
+                Some(ExportContent::File(file)) => (file.open(&self.token).await?, file.kind()),
+                Some(ExportContent::Attachment(attachment)) => (
+                    attachment.open(&self.token).await?,
+                    FileKind::from_mime_type(&attachment.mime_type),
+                ),
+            };
+            let compression = match kind {
+                FileKind::IMAGE | FileKind::VIDEO | FileKind::AUDIO => Compression::Stored,
+                _ => Compression::Deflate,
+            };
+            let mut writer = zip
+                .write_entry_stream(builder(&entry.path, entry, compression))
+                .await
+                .map_err(zip_error)?;
+            while let Some(chunk) = download.stream.next().await {
+                writer
+                    .write_all(&chunk?)
+                    .await
+                    .map_err(SduiError::IOError)?;
+            }
+            writer.close().await.map_err(zip_error)?;
+        }
+        Ok(zip.close().await.map_err(zip_error)?.into_inner())
+    }
+
+    fn push_folder(&mut self, path: &str, uuid: Option<String>, cloud_id: u64) -> String {
+        let path = self.unique(path);
+        self.entries.push(ExportEntry {
+            path: path.clone(),
+            size: 0,
+            modified_at: None,
+            source: ExportSource::Folder { uuid, cloud_id },
+            content: None,
+        });
+        path
+    }
+
+    fn push_file(&mut self, path: &str, file: File) {
+        let path = self.unique(path);
+        self.entries.push(ExportEntry {
+            path,
+            size: file.size,
+            modified_at: Some(file.updated_at.clone()),
+            source: ExportSource::File {
+                uuid: file.uuid.clone(),
+                cloud_id: file.cloud_id,
+                hash: file.hash.clone(),
+            },
+            content: Some(ExportContent::File(file)),
+        });
+    }
+
+    fn unique(&mut self, path: &str) -> String {
+        unique_name(path, &mut self.paths)
+    }
+}
+
+fn sanitize(path: &str) -> String {
+    path.split('/')
+        .filter(|segment| !segment.is_empty())
+        .map(local_name)
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn join(folder: &str, name: &str) -> String {
+    let folder = sanitize(folder);
+    let name = local_name(name);
+    if folder.is_empty() {
+        name
+    } else {
+        format!("{}/{}", folder, name)
+    }
+}
+
+fn builder(path: &str, entry: &ExportEntry, compression: Compression) -> ZipEntryBuilder {
+    let builder = ZipEntryBuilder::new(path.into(), compression);
+    match entry.modified_at.as_deref().and_then(zip_date) {
+        Some(date) => builder.last_modification_date(date.build()),
+        None => builder,
+    }
+}
+
+fn zip_date(timestamp: &str) -> Option<ZipDateTimeBuilder> {
+    let number = |range: std::ops::Range<usize>| timestamp.get(range)?.parse::<u32>().ok();
+    Some(
+        ZipDateTimeBuilder::new()
+            .year(number(0..4)? as i32)
+            .month(number(5..7)?)
+            .day(number(8..10)?)
+            .hour(number(11..13).unwrap_or(0))
+            .minute(number(14..16).unwrap_or(0))
+            .second(number(17..19).unwrap_or(0)),
+    )
+}
+
+fn zip_error(err: async_zip::error::ZipError) -> SduiError {
+    SduiError::IOError(std::io::Error::other(err))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::files::tests::file;
+
+    #[tokio::test]
+    async fn test_zip_export() {
+        let mut export = ZipExport::new("");
+        export.push_folder("Deutsch", None, 1);
+        let export = export
+            .file("Deutsch", &file("Gedicht.pdf", "file"))
+            .file("Deutsch", &file("Gedicht.pdf", "file"));
+        assert_eq!(
+            export
+                .entries()
+                .iter()
+                .map(|entry| entry.path.as_str())
+                .collect::<Vec<_>>(),
+            ["Deutsch", "Deutsch/Gedicht.pdf", "Deutsch/Gedicht (2).pdf"]
+        );
+        assert!(export.manifest().unwrap().contains("\"type\": \"file\""));
+        assert_eq!(join("../Deutsch", ".."), "_/Deutsch/_");
+        assert_eq!(join("", "a\\..\\b.pdf"), "a_.._b.pdf");
+
+        let date = zip_date("2023-05-08 08:30:15").unwrap().build();
+        assert_eq!((date.year(), date.month(), date.day()), (2023, 5, 8));
+        assert_eq!((date.hour(), date.minute(), date.second()), (8, 30, 14));
+
+        let mut folders = ZipExport::new("");
+        folders.push_folder("Deutsch", None, 1);
+        let archive = folders.write(vec![]).await.unwrap();
+        assert!(archive.starts_with(b"PK\x03\x04"));
+    }
+}
@@ -0,0 +1,203 @@
+use serde_json::json;
+
+use crate::{cloud::Cloud, files::File, prelude::*};
+
+impl Cloud {
+    pub async fn create_folder(
+        &self,
+        token: &str,
+        name: &str,
+        parent: Option<&File>,
+        protected: bool,
+    ) -> SduiResult<File> {
+        self.validate_folder(name, parent, protected)?;
+        send(
+            CLIENT
+                .post(format!(
+                    "https://api.sdui.app/v1/users/self/channels/cloud/{}/folders",
+                    self.id
+                ))
+                .bearer_auth(token)
+                .json(&json!({
+                    "name": name,
+                    "parent_id": parent.map(|parent| &parent.uuid),
+                    "is_protected_folder": u8::from(protected),
+                })),
+        )
+        .await
+    }
+
+    pub fn validate_folder(
+        &self,
+        name: &str,
+        parent: Option<&File>,
+        protected: bool,
+    ) -> Result<(), SduiError> {
+        if self.can.upload == 0 || (protected && self.can.create_protected_folder == 0) {
+            return Err(SduiError::PermissionDenied);
+        }
+        validate_name(name)?;
+        match parent {
+            Some(parent) => self.validate_target(parent),
+            None => Ok(()),
+        }
+    }
+
+    fn validate_target(&self, folder: &File) -> Result<(), SduiError> {
+        if !folder.is_folder() {
+            return Err(SduiError::InvalidTarget(folder.name.clone()));
+        }
+        if folder.cloud_id != self.id {
+            return Err(SduiError::PermissionDenied);
+        }
+        Ok(())
+    }
+
+    fn validate_write(&self, file: &File) -> Result<(), SduiError> {
+        if self.can.upload == 0
+            || (file.is_protected_folder != 0 && self.can.create_protected_folder == 0)
+        {
+            Err(SduiError::PermissionDenied)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl File {
+    pub fn is_folder(&self) -> bool {
+        self.file_type.eq_ignore_ascii_case("folder")
+    }
+
+    pub async fn rename(&self, token: &str, name: &str) -> SduiResult<File> {
+        let uri = self.validate_rename(name)?;
+        send(CLIENT.put(uri).bearer_auth(token).json(&json!({
+            "uuid": self.uuid,
+            "name": name,
+        })))
+        .await
+    }
+
+    pub async fn move_to(&self, token: &str, folder: Option<&File>) -> SduiResult<File> {
+        self.validate_move(folder)?;
+        send(
+            CLIENT
+                .put(self.api_uri())
+                .bearer_auth(token)
+                .json(&json!({ "parent_id": folder.map(|folder| &folder.uuid) })),
+        )
+        .await
+    }
+
+    pub async fn trash(&self, token: &str) -> SduiResult<File> {
+        self.cloud.validate_write(self)?;
+        send(CLIENT.delete(self.api_uri()).bearer_auth(token)).await
+    }
+
+    pub async fn delete(&self, token: &str) -> SduiResult<()> {
+        self.cloud.validate_write(self)?;
+        send_empty(
+            CLIENT
+                .delete(self.api_uri())
+                .query(&[("force", "true")])
+                .bearer_auth(token),
+        )
+        .await
+    }
+
+    pub fn validate_rename(&self, name: &str) -> Result<String, SduiError> {
+        self.cloud.validate_write(self)?;
+        validate_name(name)?;
+        self.cloud
+            .meta
+            .rename
+            .as_ref()
+            .map(|rename| rename.replace("{uuid}", &self.uuid))
+            .ok_or(SduiError::PermissionDenied)
+    }
+
+    pub fn validate_move(&self, folder: Option<&File>) -> Result<(), SduiError> {
+        self.cloud.validate_write(self)?;
+        let Some(folder) = folder else {
+            return Ok(());
+        };
+        self.cloud.validate_target(folder)?;
+        let mut ancestor = Some(folder);
+        while let Some(file) = ancestor {
+            if file.uuid == self.uuid {
+                return Err(SduiError::InvalidTarget(folder.name.clone()));
+            }
+            ancestor = file.parent.as_deref();
+        }
+        Ok(())
+    }
+
+    fn api_uri(&self) -> String {
+        format!(
+            "https://api.sdui.app/v1/users/self/channels/cloud/{}/files/{}",
+            self.cloud_id, self.uuid
+        )
+    }
+}
+
+fn validate_name(name: &str) -> Result<(), SduiError> {
+    let trimmed = name.trim();
+    if trimmed.is_empty() || trimmed == "." || trimmed == ".." || name.contains(['/', '\\']) {
+        Err(SduiError::InvalidName(name.to_owned()))
+    } else {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::files::tests::file;
+
+    #[test]
+    fn test_validate_folder_operations() {
+        let mut folder = file("folder", "folder");
+        let document = file("document", "file");
+        assert!(folder.cloud.validate_folder("Woche 3", None, false).is_ok());
+        assert!(matches!(
+            folder.cloud.validate_folder("Woche 3", None, true),
+            Err(SduiError::PermissionDenied)
+        ));
+        assert!(matches!(
+            folder
+                .cloud
+                .validate_folder("Woche/3", Some(&folder), false),
+            Err(SduiError::InvalidName(_))
+        ));
+        assert!(matches!(
+            folder
+                .cloud
+                .validate_folder("Woche 3", Some(&document), false),
+            Err(SduiError::InvalidTarget(_))
+        ));
+
+        assert!(document.validate_move(Some(&folder)).is_ok());
+        let mut child = file("child", "folder");
+        child.parent = Some(Box::new(folder.clone()));
+        assert!(matches!(
+            folder.validate_move(Some(&child)),
+            Err(SduiError::InvalidTarget(_))
+        ));
+
+        assert!(matches!(
+            document.validate_rename("Arbeitsblatt.pdf"),
+            Err(SduiError::PermissionDenied)
+        ));
+        folder.cloud.meta.rename =
+            Some("https://api.sdui.app/v1/cloud/files/{uuid}/rename".to_owned());
+        assert_eq!(
+            folder.validate_rename("Mathe").unwrap(),
+            "https://api.sdui.app/v1/cloud/files/folder/rename"
+        );
+        folder.is_protected_folder = 1;
+        assert!(matches!(
+            folder.validate_rename("Mathe"),
+            Err(SduiError::PermissionDenied)
+        ));
+    }
+}
@@ -1,8 +1,19 @@
-use bytes::Bytes;
+use std::fmt::Display;
+
 use serde::{Deserialize, Serialize};
 
 use crate::{cloud::Cloud, prelude::*, user::PartialSduiUser};
 
+mod download;
+#[cfg(feature = "zip")]
+mod export;
+mod manage;
+mod preview;
+pub use crate::files::download::*;
+#[cfg(feature = "zip")]
+pub use crate::files::export::*;
+pub use crate::files::preview::*;
+
 pub struct FileRequest {
     token: String,
     page: u64,
@@ -18,11 +29,11 @@ pub enum OrderDirection {
     DESCENDING,
 }
 
-impl ToString for OrderDirection {
-    fn to_string(&self) -> String {
+impl Display for OrderDirection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            OrderDirection::ASCENDING => "asc".to_owned(),
-            OrderDirection::DESCENDING => "desc".to_owned(),
+            OrderDirection::ASCENDING => f.write_str("asc"),
+            OrderDirection::DESCENDING => f.write_str("desc"),
         }
     }
 }
@@ -33,13 +44,13 @@ pub enum OrderBy {
     CREATEDAT,
 }
 
-impl ToString for OrderBy {
-    fn to_string(&self) -> String {
+impl Display for OrderBy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            OrderBy::NAME => "name".to_owned(),
-            OrderBy::SIZE => "size".to_owned(),
-            OrderBy::TYPE => "type".to_owned(),
-            OrderBy::CREATEDAT => "created_at".to_owned(),
+            OrderBy::NAME => f.write_str("name"),
+            OrderBy::SIZE => f.write_str("size"),
+            OrderBy::TYPE => f.write_str("type"),
+            OrderBy::CREATEDAT => f.write_str("created_at"),
         }
     }
 }
@@ -91,7 +102,7 @@ impl FileRequest {
         request(
             &format!(
                 "https://api.sdui.app/v1/users/self/channels/chats?file={}&order-dir={}&order-by={}&page={}&search={}&limit={}",
-                self.parent.map_or_else(|| "".to_owned(), |v| v.uuid),self.order_direction.to_string(), self.order_by.to_string(), self.page, self.search, self.limit
+                self.parent.map_or_else(|| "".to_owned(), |v| v.uuid),self.order_direction, self.order_by, self.page, self.search, self.limit
             ),
             &self.token,
         )
@@ -135,9 +146,6 @@ pub struct File {
 }
 
 impl File {
-    pub async fn download(&self) -> Result<Bytes, SduiError> {
-        download(&self.meta.download_uri).await
-    }
     pub async fn content(&self, token: &str) -> SduiResult<Vec<File>> {
         request(&self.meta.content_uri, token).await
     }
@@ -165,3 +173,80 @@ pub struct FileMeta {
     pub uri: String,
     pub username: Option<String>,
 }
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::cloud::{CloudCan, CloudMeta};
+
+    pub(crate) fn file(uuid: &str, file_type: &str) -> File {
+        File {
+            cloud: Cloud {
+                can: CloudCan {
+                    upload: 1,
+                    create_protected_folder: 0,
+                },
+                disabled_at: None,
+                id: 1,
+                meta: CloudMeta {
+                    download: None,
+                    forbidden: vec![],
+                    max_number: 10,
+                    rename: None,
+                    upload: None,
+                    upload_limit: None,
+                },
+                updated_at: None,
+            },
+            cloud_id: 1,
+            created_at: "2023-05-08 08:00:00".to_owned(),
+            deleted_at: None,
+            description: None,
+            disk_id: None,
+            duration_in_seconds: None,
+            expires_at: None,
+            extension: None,
+            file_type: file_type.to_owned(),
+            has_thumbnail: None,
+            hash: None,
+            is_collaborative: false,
+            is_protected_folder: 0,
+            meta: FileMeta {
+                absolute_path: format!("/{}", uuid),
+                content_uri: String::new(),
+                details_uri: String::new(),
+                download_uri: String::new(),
+                edit_access_token: None,
+                edit_uri: None,
+                files_count: 0,
+                has_audo: 0,
+                has_image: 0,
+                has_text_document: 0,
+                has_thumbnail: 0,
+                has_video: 0,
+                has_wopi_support: 0,
+                location: String::new(),
+                parent: None,
+                subtitle: None,
+                thumbnail_uri: None,
+                uri: String::new(),
+                username: None,
+            },
+            name: uuid.to_owned(),
+            parent: None,
+            parent_id: String::new(),
+            path: format!("/{}", uuid),
+            referenced_permissions: None,
+            referenced_until: None,
+            referenced_uuid: None,
+            reserved: None,
+            size: 0,
+            mime_type: None,
+            updated_at: "2023-05-08 08:00:00".to_owned(),
+            upload_limited_at: None,
+            user: None,
+            user_id: None,
+            uuid: uuid.to_owned(),
+        }
+    }
+}
@@ -0,0 +1,182 @@
+use bytes::Bytes;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    files::{open_download, File},
+    prelude::*,
+};
+
+#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum FileKind {
+    FOLDER,
+    IMAGE,
+    VIDEO,
+    AUDIO,
+    DOCUMENT,
+    OTHER,
+}
+
+impl FileKind {
+    pub fn as_str(&self) -> &str {
+        match self {
+            FileKind::FOLDER => "folder",
+            FileKind::IMAGE => "image",
+            FileKind::VIDEO => "video",
+            FileKind::AUDIO => "audio",
+            FileKind::DOCUMENT => "document",
+            FileKind::OTHER => "other",
+        }
+    }
+
+    pub fn from_mime_type(mime_type: &str) -> Self {
+        let mime_type = mime_type.to_ascii_lowercase();
+        let (kind, subtype) = mime_type.split_once('/').unwrap_or((&mime_type, ""));
+        match kind {
+            "image" => FileKind::IMAGE,
+            "video" => FileKind::VIDEO,
+            "audio" => FileKind::AUDIO,
+            "text" => FileKind::DOCUMENT,
+            "application"
+                if subtype == "pdf"
+                    || subtype == "msword"
+                    || subtype == "rtf"
+                    || subtype.starts_with("vnd.ms-")
+                    || subtype.starts_with("vnd.openxmlformats-officedocument")
+                    || subtype.starts_with("vnd.oasis.opendocument") =>
+            {
+                FileKind::DOCUMENT
+            }
+            _ => FileKind::OTHER,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Hash, Eq, PartialEq, PartialOrd, Ord)]
+pub struct Preview {
+    pub content_type: Option<String>,
+    pub data: Bytes,
+    pub generated: bool,
+}
+
+impl File {
+    pub fn kind(&self) -> FileKind {
+        if self.is_folder() {
+            FileKind::FOLDER
+        } else if self.meta.has_image != 0 {
+            FileKind::IMAGE
+        } else if self.meta.has_video != 0 {
+            FileKind::VIDEO
+        } else if self.meta.has_audo != 0 {
+            FileKind::AUDIO
+        } else if self.meta.has_text_document != 0 {
+            FileKind::DOCUMENT
+        } else {
+            self.mime_type
+                .as_deref()
+                .map_or(FileKind::OTHER, FileKind::from_mime_type)
+        }
+    }
+
+    pub fn thumbnail_uri(&self, width: u32, height: u32) -> Option<String> {
+        let uri = self
+            .meta
+            .thumbnail_uri
+            .as_deref()
+            .filter(|uri| !uri.is_empty())?;
+        if self.meta.has_thumbnail == 0 && self.has_thumbnail != Some(true) {
+            return None;
+        }
+        let separator = if uri.contains('?') { '&' } else { '?' };
+        Some(format!(
+            "{}{}width={}&height={}",
+            uri, separator, width, height
+        ))
+    }
+
+    pub async fn thumbnail(
+        &self,
+        token: &str,
+        width: u32,
+        height: u32,
+    ) -> Result<Preview, SduiError> {
+        let uri = self
+            .thumbnail_uri(width, height)
+            .ok_or(SduiError::NoPreview)?;
+        let download = open_download(&uri, Some(token)).await?;
+        Ok(Preview {
+            content_type: download.content_type.clone(),
+            data: download.bytes().await?,
+            generated: false,
+        })
+    }
+
+    pub async fn preview(
+        &self,
+        token: &str,
+        width: u32,
+        height: u32,
+    ) -> Result<Preview, SduiError> {
+        if self.thumbnail_uri(width, height).is_some() {
+            return self.thumbnail(token, width, height).await;
+        }
+        #[cfg(feature = "preview")]
+        if self.kind() == FileKind::IMAGE {
+            let data = self.open(token).await?.bytes().await?;
+            return Ok(Preview {
+                content_type: Some("image/png".to_owned()),
+                data: generate_preview(&data, width, height)?,
+                generated: true,
+            });
+        }
+        Err(SduiError::NoPreview)
+    }
+}
+
+#[cfg(feature = "preview")]
+pub fn generate_preview(data: &[u8], width: u32, height: u32) -> Result<Bytes, SduiError> {
+    let image =
+        image::load_from_memory(data).map_err(|err| SduiError::ImageError(err.to_string()))?;
+    let mut output = std::io::Cursor::new(vec![]);
+    image
+        .thumbnail(width, height)
+        .write_to(&mut output, image::ImageOutputFormat::Png)
+        .map_err(|err| SduiError::ImageError(err.to_string()))?;
+    Ok(output.into_inner().into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::files::tests::file;
+
+    #[test]
+    fn test_file_kind() {
+        assert_eq!(file("folder", "folder").kind(), FileKind::FOLDER);
+        let mut document = file("document", "file");
+        assert_eq!(document.kind(), FileKind::OTHER);
+        document.mime_type = Some("application/vnd.oasis.opendocument.text".to_owned());
+        assert_eq!(document.kind(), FileKind::DOCUMENT);
+        document.meta.has_video = 1;
+        assert_eq!(document.kind(), FileKind::VIDEO);
+
+        assert_eq!(document.thumbnail_uri(64, 64), None);
+        document.meta.has_thumbnail = 1;
+        document.meta.thumbnail_uri = Some("https://api.sdui.app/thumb?v=1".to_owned());
+        assert_eq!(
+            document.thumbnail_uri(64, 48).as_deref(),
+            Some("https://api.sdui.app/thumb?v=1&width=64&height=48")
+        );
+    }
+
+    #[cfg(feature = "preview")]
+    #[test]
+    fn test_generate_preview() {
+        let mut source = std::io::Cursor::new(vec![]);
+        image::DynamicImage::new_rgb8(400, 200)
+            .write_to(&mut source, image::ImageOutputFormat::Png)
+            .unwrap();
+        let preview = generate_preview(source.get_ref(), 100, 100).unwrap();
+        let preview = image::load_from_memory(&preview).unwrap();
+        assert_eq!((preview.width(), preview.height()), (100, 50));
+    }
+}
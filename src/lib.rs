@@ -2,6 +2,7 @@
 extern crate lazy_static;
 
 pub mod auth;
+pub mod calendar;
 pub mod channel;
 pub mod chat;
 pub mod cloud;
@@ -10,8 +11,12 @@ pub mod grade;
 pub mod news;
 #[macro_use]
 pub mod prelude;
+#[cfg(feature = "realtime")]
+pub mod realtime;
 pub mod timetable;
 pub mod user;
+#[cfg(feature = "webdav")]
+pub mod webdav;
 
 #[cfg(test)]
 mod tests {
@@ -50,6 +50,7 @@ pub struct News {
 
 #[derive(Debug, Clone, PartialEq, PartialOrd, Serialize, Deserialize)]
 #[serde(untagged)]
+#[allow(clippy::large_enum_variant)]
 pub enum NewsPreview {
     Attachment(Attachment),
     String(String),
@@ -1,8 +1,7 @@
 use bytes::Bytes;
-
 use reqwest::{
     header::{HeaderMap, HeaderValue},
-    StatusCode,
+    RequestBuilder, Response, StatusCode,
 };
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
@@ -54,6 +53,20 @@ impl RateLimit {
             remaining: self.remaining.min(other.remaining),
         }
     }
+
+    pub(crate) fn unknown() -> RateLimit {
+        RateLimit {
+            limit: 0,
+            remaining: 0,
+        }
+    }
+}
+
+pub(crate) fn join_rate_limit(rate_limit: &mut Option<RateLimit>, other: RateLimit) {
+    *rate_limit = Some(match rate_limit.take() {
+        Some(rate_limit) => rate_limit.join(other),
+        None => other,
+    });
 }
 
 #[derive(Debug)]
@@ -62,6 +75,24 @@ pub enum SduiError {
     JSONError,
     NotLoggedIn,
     LoginError,
+    IOError(std::io::Error),
+    InvalidDate(String),
+    PermissionDenied,
+    ForbiddenExtension(String),
+    FileTooLarge { size: u64, limit: u64 },
+    InvalidName(String),
+    InvalidTarget(String),
+    HashMismatch { expected: String, actual: String },
+    Expired,
+    NoPreview,
+    ImageError(String),
+    EmptyMessage,
+}
+
+impl From<reqwest::Error> for SduiError {
+    fn from(err: reqwest::Error) -> Self {
+        SduiError::RequestError(err)
+    }
 }
 pub type GenericSduiResponse = SduiResponse<serde_json::Value>;
 
@@ -77,6 +108,8 @@ pub struct SduiMeta {
     pub warnings: serde_json::Value,
     pub errors: serde_json::Value,
     pub success: serde_json::Value,
+    #[serde(default)]
+    pub last_page: Option<u64>,
 }
 
 #[derive(Debug, Clone, Hash, Eq, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
@@ -116,33 +149,63 @@ impl School {
     }
 }
 
-pub async fn download(url: &str) -> Result<Bytes, SduiError> {
-    let response = CLIENT
-        .get(url)
-        .send()
-        .await
-        .map_err(SduiError::RequestError)?;
-    if response.status() == StatusCode::UNAUTHORIZED {
-        Err(SduiError::NotLoggedIn)
+pub(crate) fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+pub(crate) fn escape_csv(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
     } else {
-        response.bytes().await.map_err(SduiError::RequestError)
+        field.to_owned()
     }
 }
 
+pub async fn download(url: &str) -> Result<Bytes, SduiError> {
+    crate::files::download_url(url, None).await
+}
+
 pub async fn request<T: DeserializeOwned>(url: &str, token: &str) -> SduiResult<T> {
-    let response = CLIENT
-        .get(url)
-        .bearer_auth(token)
-        .send()
-        .await
-        .map_err(SduiError::RequestError)?;
-    if response.status() == StatusCode::UNAUTHORIZED {
-        return Err(SduiError::NotLoggedIn);
-    }
+    send(CLIENT.get(url).bearer_auth(token)).await
+}
+
+pub(crate) async fn send<T: DeserializeOwned>(request: RequestBuilder) -> SduiResult<T> {
+    let (response, rate_limit) = send_response::<T>(request).await?;
+    Ok((response.data, rate_limit))
+}
+
+pub(crate) async fn send_response<T: DeserializeOwned>(
+    request: RequestBuilder,
+) -> SduiResult<SduiResponse<T>> {
+    let response = check_status(request.send().await.map_err(SduiError::RequestError)?)?;
     let rate_limit = RateLimit::from_headers(response.headers());
     let data = response
         .json::<SduiResponse<T>>()
         .await
         .map_err(SduiError::RequestError)?;
-    Ok((data.data, rate_limit))
+    Ok((data, rate_limit))
+}
+
+pub(crate) async fn send_empty(request: RequestBuilder) -> SduiResult<()> {
+    let response = check_status(request.send().await.map_err(SduiError::RequestError)?)?;
+    Ok(((), RateLimit::from_headers(response.headers())))
+}
+
+fn check_status(response: Response) -> Result<Response, SduiError> {
+    match response.status() {
+        StatusCode::UNAUTHORIZED => Err(SduiError::NotLoggedIn),
+        StatusCode::FORBIDDEN => Err(SduiError::PermissionDenied),
+        _ => response.error_for_status().map_err(SduiError::RequestError),
+    }
 }
@@ -0,0 +1,411 @@
+use std::time::{Duration, Instant};
+
+use futures::{stream::BoxStream, SinkExt, StreamExt};
+use serde::{Deserialize, Serialize};
+use tokio::net::TcpStream;
+use tokio_tungstenite::{
+    connect_async,
+    tungstenite::{self, Message as Frame},
+    MaybeTlsStream, WebSocketStream,
+};
+
+use crate::{
+    chat::{Chat, Message},
+    news::News,
+    prelude::*,
+};
+
+type Socket = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+const STABLE_CONNECTION: Duration = Duration::from_secs(30);
+
+pub struct RealtimeSubscriber {
+    token: String,
+    url: String,
+    channels: Vec<String>,
+    retry_delay: Duration,
+    max_retry_delay: Duration,
+    max_retries: Option<u32>,
+    idle_timeout: Duration,
+    auth_timeout: Duration,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "data")]
+#[allow(clippy::large_enum_variant)]
+pub enum RealtimeEvent {
+    #[serde(rename = "message.created")]
+    MessageCreated(Message),
+    #[serde(rename = "message.edited")]
+    MessageEdited(Message),
+    #[serde(rename = "message.deleted")]
+    MessageDeleted { chat_id: u64, message_id: u64 },
+    #[serde(rename = "news.published")]
+    NewsPublished(News),
+    #[serde(rename = "chat.updated")]
+    ChatUpdated(Chat),
+    #[serde(rename = "reconnected")]
+    Reconnected,
+    #[serde(skip)]
+    Unknown(String),
+}
+
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum Command<'a> {
+    Auth { token: &'a str },
+    Subscribe { channel: &'a str },
+}
+
+#[derive(Deserialize)]
+struct Reply {
+    #[serde(rename = "type")]
+    kind: String,
+}
+
+struct Connection {
+    subscriber: RealtimeSubscriber,
+    socket: Option<Socket>,
+    failures: u32,
+    connected: bool,
+    connected_at: Option<Instant>,
+    pinged: bool,
+}
+
+impl RealtimeSubscriber {
+    pub fn new(token: &str) -> Self {
+        RealtimeSubscriber {
+            token: token.to_owned(),
+            url: "wss://realtime.sdui.app/ws".to_owned(),
+            channels: vec!["users.self".to_owned()],
+            retry_delay: Duration::from_secs(1),
+            max_retry_delay: Duration::from_secs(60),
+            max_retries: None,
+            idle_timeout: Duration::from_secs(60),
+            auth_timeout: Duration::from_secs(10),
+        }
+    }
+
+    pub fn url(mut self, url: &str) -> Self {
+        self.url = url.to_owned();
+        self
+    }
+
+    pub fn chat(mut self, chat_id: u64) -> Self {
+        self.channels.push(format!("chats.{}", chat_id));
+        self
+    }
+
+    pub fn news(mut self) -> Self {
+        self.channels.push("news".to_owned());
+        self
+    }
+
+    pub fn retry_delay(mut self, retry_delay: Duration, max_retry_delay: Duration) -> Self {
+        self.retry_delay = retry_delay;
+        self.max_retry_delay = max_retry_delay;
+        self
+    }
+
+    pub fn max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = Some(max_retries);
+        self
+    }
+
+    pub fn idle_timeout(mut self, idle_timeout: Duration) -> Self {
+        self.idle_timeout = idle_timeout;
+        self
+    }
+
+    pub fn auth_timeout(mut self, auth_timeout: Duration) -> Self {
+        self.auth_timeout = auth_timeout;
+        self
+    }
+
+    pub fn subscribe(self) -> BoxStream<'static, Result<RealtimeEvent, SduiError>> {
+        let connection = Connection {
+            subscriber: self,
+            socket: None,
+            failures: 0,
+            connected: false,
+            connected_at: None,
+            pinged: false,
+        };
+        futures::stream::unfold(Some(connection), |connection| async move {
+            let mut connection = connection?;
+            loop {
+                let socket = match &mut connection.socket {
+                    Some(socket) => socket,
+                    None => {
+                        if connection.failures > 0 {
+                            tokio::time::sleep(connection.subscriber.backoff(connection.failures))
+                                .await;
+                        }
+                        match connection.subscriber.connect().await {
+                            Ok(socket) => {
+                                connection.socket = Some(socket);
+                                connection.connected_at = Some(Instant::now());
+                                if std::mem::replace(&mut connection.connected, true) {
+                                    return Some((
+                                        Ok(RealtimeEvent::Reconnected),
+                                        Some(connection),
+                                    ));
+                                }
+                            }
+                            Err(SduiError::NotLoggedIn) => {
+                                return Some((Err(SduiError::NotLoggedIn), None));
+                            }
+                            Err(err) => {
+                                connection.failures += 1;
+                                if connection
+                                    .subscriber
+                                    .max_retries
+                                    .is_some_and(|max_retries| connection.failures > max_retries)
+                                {
+                                    return Some((Err(err), None));
+                                }
+                            }
+                        }
+                        continue;
+                    }
+                };
+                match tokio::time::timeout(connection.subscriber.idle_timeout, socket.next()).await
+                {
+                    Err(_) if !connection.pinged => {
+                        connection.pinged = true;
+                        if socket.send(Frame::Ping(vec![])).await.is_err() {
+                            connection.disconnect();
+                        }
+                    }
+                    Err(_) => connection.disconnect(),
+                    Ok(frame) => {
+                        connection.pinged = false;
+                        match frame {
+                            Some(Ok(Frame::Text(text))) => {
+                                let event = serde_json::from_str(&text)
+                                    .unwrap_or(RealtimeEvent::Unknown(text));
+                                return Some((Ok(event), Some(connection)));
+                            }
+                            Some(Ok(Frame::Close(_))) | Some(Err(_)) | None => {
+                                connection.disconnect()
+                            }
+                            Some(Ok(_)) => {}
+                        }
+                    }
+                }
+            }
+        })
+        .boxed()
+    }
+
+    async fn connect(&self) -> Result<Socket, SduiError> {
+        let (mut socket, _) = connect_async(self.url.as_str())
+            .await
+            .map_err(websocket_error)?;
+        send_command(&mut socket, Command::Auth { token: &self.token }).await?;
+        tokio::time::timeout(self.auth_timeout, authenticate(&mut socket))
+            .await
+            .map_err(|_| SduiError::IOError(std::io::ErrorKind::TimedOut.into()))??;
+        for channel in &self.channels {
+            send_command(&mut socket, Command::Subscribe { channel }).await?;
+        }
+        Ok(socket)
+    }
+
+    fn backoff(&self, failures: u32) -> Duration {
+        self.retry_delay
+            .saturating_mul(2u32.saturating_pow(failures.saturating_sub(1)))
+            .min(self.max_retry_delay)
+    }
+}
+
+impl Connection {
+    fn disconnect(&mut self) {
+        self.socket = None;
+        self.pinged = false;
+        if self
+            .connected_at
+            .take()
+            .is_some_and(|connected_at| connected_at.elapsed() >= STABLE_CONNECTION)
+        {
+            self.failures = 0;
+        } else {
+            self.failures += 1;
+        }
+    }
+}
+
+async fn authenticate(socket: &mut Socket) -> Result<(), SduiError> {
+    loop {
+        match socket.next().await {
+            Some(Ok(Frame::Text(text))) => {
+                match serde_json::from_str::<Reply>(&text).map(|reply| reply.kind) {
+                    Ok(kind) if kind == "auth.success" => return Ok(()),
+                    Ok(kind) if kind == "auth.failed" => return Err(SduiError::NotLoggedIn),
+                    _ => {}
+                }
+            }
+            Some(Ok(_)) => {}
+            Some(Err(err)) => return Err(websocket_error(err)),
+            None => {
+                return Err(SduiError::IOError(
+                    std::io::ErrorKind::ConnectionAborted.into(),
+                ))
+            }
+        }
+    }
+}
+
+async fn send_command(socket: &mut Socket, command: Command<'_>) -> Result<(), SduiError> {
+    let text = serde_json::to_string(&command).map_err(|_| SduiError::JSONError)?;
+    socket
+        .send(Frame::Text(text))
+        .await
+        .map_err(websocket_error)
+}
+
+fn websocket_error(err: tungstenite::Error) -> SduiError {
+    match err {
+        tungstenite::Error::Http(response) if response.status() == 401 => SduiError::NotLoggedIn,
+        err => SduiError::IOError(std::io::Error::other(err)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chat::tests::chat;
+    use tokio::net::TcpListener;
+
+    async fn accept(listener: &TcpListener) -> (WebSocketStream<TcpStream>, Vec<String>) {
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut socket = tokio_tungstenite::accept_async(stream).await.unwrap();
+        let auth = socket.next().await.unwrap().unwrap().into_text().unwrap();
+        let reply = if auth.contains("\"token\":\"secret\"") {
+            "auth.success"
+        } else {
+            "auth.failed"
+        };
+        socket
+            .send(Frame::Text(format!("{{\"type\":\"{}\"}}", reply)))
+            .await
+            .unwrap();
+        let mut commands = vec![auth];
+        if reply == "auth.success" {
+            for _ in 0..2 {
+                commands.push(socket.next().await.unwrap().unwrap().into_text().unwrap());
+            }
+        }
+        (socket, commands)
+    }
+
+    #[tokio::test]
+    async fn test_realtime_reconnect() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        let server = tokio::spawn(async move {
+            let (mut socket, commands) = accept(&listener).await;
+            assert_eq!(commands[2], r#"{"type":"subscribe","channel":"chats.3"}"#);
+            socket
+                .send(Frame::Text(
+                    r#"{"type":"message.deleted","data":{"chat_id":3,"message_id":12}}"#.into(),
+                ))
+                .await
+                .unwrap();
+            socket
+                .send(Frame::Text("{\"type\":\"typing\"}".into()))
+                .await
+                .unwrap();
+            socket.close(None).await.unwrap();
+
+            let (mut socket, commands) = accept(&listener).await;
+            assert_eq!(commands[2], r#"{"type":"subscribe","channel":"chats.3"}"#);
+            let event = serde_json::to_string(&RealtimeEvent::ChatUpdated(chat(3))).unwrap();
+            socket.send(Frame::Text(event)).await.unwrap();
+            socket.close(None).await.unwrap();
+            accept(&listener).await;
+        });
+
+        let mut events = RealtimeSubscriber::new("secret")
+            .url(&url)
+            .chat(3)
+            .retry_delay(Duration::from_millis(10), Duration::from_millis(50))
+            .subscribe();
+        assert_eq!(
+            events.next().await.unwrap().unwrap(),
+            RealtimeEvent::MessageDeleted {
+                chat_id: 3,
+                message_id: 12
+            }
+        );
+        assert_eq!(
+            events.next().await.unwrap().unwrap(),
+            RealtimeEvent::Unknown("{\"type\":\"typing\"}".to_owned())
+        );
+        assert_eq!(
+            events.next().await.unwrap().unwrap(),
+            RealtimeEvent::Reconnected
+        );
+        assert_eq!(
+            events.next().await.unwrap().unwrap(),
+            RealtimeEvent::ChatUpdated(chat(3))
+        );
+        assert_eq!(
+            events.next().await.unwrap().unwrap(),
+            RealtimeEvent::Reconnected
+        );
+        server.await.unwrap();
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        let server = tokio::spawn(async move { accept(&listener).await });
+        let mut events = RealtimeSubscriber::new("wrong").url(&url).subscribe();
+        assert!(matches!(
+            events.next().await,
+            Some(Err(SduiError::NotLoggedIn))
+        ));
+        assert!(events.next().await.is_none());
+        server.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_realtime_timeouts() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        let server = tokio::spawn(async move {
+            let (silent, _) = accept(&listener).await;
+            let (_socket, _) = accept(&listener).await;
+            drop(silent);
+        });
+        let mut events = RealtimeSubscriber::new("secret")
+            .url(&url)
+            .chat(3)
+            .idle_timeout(Duration::from_millis(50))
+            .retry_delay(Duration::from_millis(10), Duration::from_millis(50))
+            .subscribe();
+        assert_eq!(
+            events.next().await.unwrap().unwrap(),
+            RealtimeEvent::Reconnected
+        );
+        server.await.unwrap();
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        let server = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut socket = tokio_tungstenite::accept_async(stream).await.unwrap();
+            socket.next().await;
+            socket
+        });
+        let mut events = RealtimeSubscriber::new("secret")
+            .url(&url)
+            .auth_timeout(Duration::from_millis(50))
+            .max_retries(0)
+            .subscribe();
+        assert!(matches!(
+            events.next().await,
+            Some(Err(SduiError::IOError(err))) if err.kind() == std::io::ErrorKind::TimedOut
+        ));
+        server.await.unwrap();
+    }
+}
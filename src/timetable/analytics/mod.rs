@@ -0,0 +1,333 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use crate::timetable::*;
+
+pub async fn get_timetable_statistics(
+    token: &str,
+    target: &TimetableTarget,
+    begin: &Date,
+    end: &Date,
+) -> SduiResult<TimeTableStatistics> {
+    let (timetable, rate_limit) = get_timetable_range(token, target, begin, end).await?;
+    Ok((
+        TimeTableStatistics::from_lessons(&timetable.lessons),
+        rate_limit,
+    ))
+}
+
+#[derive(Debug, Clone, Default, Hash, Eq, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct LessonCounts {
+    pub scheduled: u64,
+    pub delivered: u64,
+    pub cancelled: u64,
+    pub substituted: u64,
+    pub additional: u64,
+    pub other: u64,
+    pub scheduled_minutes: u64,
+    pub delivered_minutes: u64,
+}
+
+impl LessonCounts {
+    fn count(&mut self, lesson: &Lesson) {
+        let minutes = lesson.ends_at.saturating_sub(lesson.begins_at) / 60;
+        let (scheduled, delivered) = match lesson.kind {
+            LessonKind::NORMAL => (true, true),
+            LessonKind::SUBSTITUTION => {
+                self.substituted += 1;
+                (true, true)
+            }
+            LessonKind::CANCLED => {
+                self.cancelled += 1;
+                (true, false)
+            }
+            LessonKind::ADDITIONAL => {
+                self.additional += 1;
+                (false, true)
+            }
+            LessonKind::Other(_) => {
+                self.other += 1;
+                (true, false)
+            }
+        };
+        if scheduled {
+            self.scheduled += 1;
+            self.scheduled_minutes += minutes;
+        }
+        if delivered {
+            self.delivered += 1;
+            self.delivered_minutes += minutes;
+        }
+    }
+}
+
+#[derive(Debug, Clone, Hash, Eq, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct SubjectStatistics {
+    pub subject: Subject,
+    pub counts: LessonCounts,
+}
+
+#[derive(Debug, Clone, Hash, Eq, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct TeacherStatistics {
+    pub teacher: Teacher,
+    pub taught: u64,
+    pub substituted: u64,
+    pub cancelled: u64,
+    pub substitutions_given: u64,
+}
+
+impl TeacherStatistics {
+    fn new(teacher: &Teacher) -> Self {
+        TeacherStatistics {
+            teacher: teacher.clone(),
+            taught: 0,
+            substituted: 0,
+            cancelled: 0,
+            substitutions_given: 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Hash, Eq, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct WeekStatistics {
+    pub week: Week,
+    pub counts: LessonCounts,
+}
+
+#[derive(Debug, Clone, Hash, Eq, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct TimeTableStatistics {
+    pub subjects: Vec<SubjectStatistics>,
+    pub teachers: Vec<TeacherStatistics>,
+    pub weeks: Vec<WeekStatistics>,
+}
+
+impl TimeTableStatistics {
+    pub fn from_lessons(lessons: &[Lesson]) -> Self {
+        let plan = SubstitutionPlan::from_lessons(lessons);
+        let substitutions: Vec<&Substitution> =
+            plan.days.iter().flat_map(|day| &day.entries).collect();
+        // A cancelled lesson that got replaced within the same subject is counted through its
+        // replacement only.
+        let subject_ids: HashMap<u64, u64> = lessons
+            .iter()
+            .map(|lesson| (lesson.id, lesson.course.subject.id))
+            .collect();
+        let replaced: HashSet<u64> = substitutions
+            .iter()
+            .filter_map(|substitution| {
+                let original = substitution.original_lesson_id?;
+                (subject_ids.get(&original) == Some(&substitution.course.subject.id))
+                    .then_some(original)
+            })
+            .collect();
+
+        let mut subjects: BTreeMap<u64, SubjectStatistics> = BTreeMap::new();
+        let mut teachers: BTreeMap<u64, TeacherStatistics> = BTreeMap::new();
+        let mut weeks: BTreeMap<Week, LessonCounts> = BTreeMap::new();
+        for lesson in lessons
+            .iter()
+            .filter(|lesson| !replaced.contains(&lesson.id))
+        {
+            subjects
+                .entry(lesson.course.subject.id)
+                .or_insert_with(|| SubjectStatistics {
+                    subject: lesson.course.subject.clone(),
+                    counts: LessonCounts::default(),
+                })
+                .counts
+                .count(lesson);
+            weeks
+                .entry(Date::from_timestamp(lesson.begins_at).week())
+                .or_default()
+                .count(lesson);
+            if !matches!(lesson.kind, LessonKind::CANCLED | LessonKind::Other(_)) {
+                for teacher in &lesson.teachers {
+                    teachers
+                        .entry(teacher.id)
+                        .or_insert_with(|| TeacherStatistics::new(teacher))
+                        .taught += 1;
+                }
+            }
+        }
+        for substitution in substitutions {
+            let absent = substitution.original_teachers.iter().filter(|teacher| {
+                !substitution
+                    .teachers
+                    .iter()
+                    .any(|other| other.id == teacher.id)
+            });
+            for teacher in absent {
+                let statistics = teachers
+                    .entry(teacher.id)
+                    .or_insert_with(|| TeacherStatistics::new(teacher));
+                match substitution.kind {
+                    LessonKind::CANCLED => statistics.cancelled += 1,
+                    _ => statistics.substituted += 1,
+                }
+            }
+            if substitution.kind == LessonKind::SUBSTITUTION {
+                let substitutes = substitution.teachers.iter().filter(|teacher| {
+                    !substitution
+                        .original_teachers
+                        .iter()
+                        .any(|other| other.id == teacher.id)
+                });
+                for teacher in substitutes {
+                    teachers
+                        .entry(teacher.id)
+                        .or_insert_with(|| TeacherStatistics::new(teacher))
+                        .substitutions_given += 1;
+                }
+            }
+        }
+
+        let mut subjects: Vec<SubjectStatistics> = subjects.into_values().collect();
+        subjects.sort_by(|a, b| a.subject.name.cmp(&b.subject.name));
+        let mut teachers: Vec<TeacherStatistics> = teachers.into_values().collect();
+        teachers.sort_by(|a, b| {
+            (b.substituted + b.cancelled)
+                .cmp(&(a.substituted + a.cancelled))
+                .then_with(|| a.teacher.shortcut.cmp(&b.teacher.shortcut))
+        });
+        TimeTableStatistics {
+            subjects,
+            teachers,
+            weeks: weeks
+                .into_iter()
+                .map(|(week, counts)| WeekStatistics { week, counts })
+                .collect(),
+        }
+    }
+
+    pub fn to_json(&self) -> Result<String, SduiError> {
+        serde_json::to_string_pretty(self).map_err(|_| SduiError::JSONError)
+    }
+
+    pub fn subjects_csv(&self) -> String {
+        let mut csv = format!("subject,{}\n", COUNTS_HEADER);
+        for subject in &self.subjects {
+            csv.push_str(&format!(
+                "{},{}\n",
+                escape_csv(&subject.subject.name),
+                counts_csv(&subject.counts)
+            ));
+        }
+        csv
+    }
+
+    pub fn teachers_csv(&self) -> String {
+        let mut csv =
+            String::from("teacher,name,taught,substituted,cancelled,substitutions_given\n");
+        for teacher in &self.teachers {
+            csv.push_str(&format!(
+                "{},{},{},{},{},{}\n",
+                escape_csv(&teacher.teacher.shortcut),
+                escape_csv(&teacher.teacher.name),
+                teacher.taught,
+                teacher.substituted,
+                teacher.cancelled,
+                teacher.substitutions_given
+            ));
+        }
+        csv
+    }
+
+    pub fn weeks_csv(&self) -> String {
+        let mut csv = format!("week,first_day,{}\n", COUNTS_HEADER);
+        for week in &self.weeks {
+            csv.push_str(&format!(
+                "{},{},{}\n",
+                week.week,
+                week.week.first_day(),
+                counts_csv(&week.counts)
+            ));
+        }
+        csv
+    }
+}
+
+const COUNTS_HEADER: &str =
+    "scheduled,delivered,cancelled,substituted,additional,other,scheduled_minutes,delivered_minutes";
+
+fn counts_csv(counts: &LessonCounts) -> String {
+    format!(
+        "{},{},{},{},{},{},{},{}",
+        counts.scheduled,
+        counts.delivered,
+        counts.cancelled,
+        counts.substituted,
+        counts.additional,
+        counts.other,
+        counts.scheduled_minutes,
+        counts.delivered_minutes
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::timetable::tests::lesson;
+
+    const DAY: u64 = 86400;
+    const MONDAY: u64 = 1683504000;
+
+    #[test]
+    fn test_statistics() {
+        let teacher = |id: u64, shortcut: &str| Teacher {
+            id,
+            name: shortcut.to_owned(),
+            shortcut: shortcut.to_owned(),
+        };
+        let mut normal = lesson(1, MONDAY + 28800, LessonKind::NORMAL);
+        normal.teachers = vec![teacher(1, "MUE")];
+        let mut cancelled = lesson(2, MONDAY + 7 * DAY + 28800, LessonKind::CANCLED);
+        cancelled.teachers = vec![teacher(1, "MUE")];
+        let mut substitution = lesson(3, MONDAY + 7 * DAY + 28800, LessonKind::SUBSTITUTION);
+        substitution.teachers = vec![teacher(2, "SCH")];
+
+        let exam = lesson(
+            4,
+            MONDAY + DAY + 28800,
+            LessonKind::Other("EXAM".to_owned()),
+        );
+
+        let statistics =
+            TimeTableStatistics::from_lessons(&[normal, cancelled, substitution, exam]);
+        assert_eq!(statistics.subjects.len(), 1);
+        let counts = &statistics.subjects[0].counts;
+        assert_eq!((counts.scheduled, counts.delivered), (3, 2));
+        assert_eq!((counts.cancelled, counts.substituted), (0, 1));
+        assert_eq!(counts.other, 1);
+        assert_eq!(statistics.weeks.len(), 2);
+        assert_eq!(statistics.weeks[0].week, Week::new(2023, 19).unwrap());
+        assert_eq!(statistics.teachers[0].teacher.shortcut, "MUE");
+        assert_eq!(statistics.teachers[0].substituted, 1);
+        assert_eq!(statistics.teachers[1].substitutions_given, 1);
+    }
+
+    #[test]
+    fn test_cross_subject_substitution() {
+        let cancelled = lesson(1, MONDAY + 28800, LessonKind::CANCLED);
+        let mut substitution = lesson(2, MONDAY + 28800, LessonKind::SUBSTITUTION);
+        substitution.course.id = 2;
+        substitution.course.subject.id = 2;
+        substitution.course.subject.name = "Deutsch".to_owned();
+
+        let lessons = [cancelled, substitution];
+        let plan = SubstitutionPlan::from_lessons(&lessons);
+        assert_eq!(plan.days[0].entries[0].original_lesson_id, Some(1));
+
+        let statistics = TimeTableStatistics::from_lessons(&lessons);
+        assert_eq!(statistics.subjects.len(), 2);
+        let deutsch = &statistics.subjects[0];
+        assert_eq!(deutsch.subject.name, "Deutsch");
+        assert_eq!(
+            (deutsch.counts.delivered, deutsch.counts.substituted),
+            (1, 1)
+        );
+        let mathe = &statistics.subjects[1].counts;
+        assert_eq!(
+            (mathe.scheduled, mathe.delivered, mathe.cancelled),
+            (1, 0, 1)
+        );
+    }
+}
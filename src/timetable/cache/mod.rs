@@ -0,0 +1,273 @@
+use std::{
+    future::Future,
+    path::PathBuf,
+    sync::atomic::{AtomicU64, Ordering},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use serde::de::DeserializeOwned;
+use tokio::{runtime::Handle, task::JoinHandle};
+
+use crate::timetable::*;
+
+#[derive(Debug, Clone, Hash, Eq, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct Cached<T> {
+    pub value: T,
+    pub fetched_at: u64,
+}
+
+impl<T> Cached<T> {
+    pub fn age(&self) -> Duration {
+        Duration::from_secs(now().saturating_sub(self.fetched_at))
+    }
+}
+
+impl Cached<TimeTable> {
+    pub fn last_updated_at(&self) -> &str {
+        &self.value.last_updated_at
+    }
+}
+
+#[derive(Debug)]
+pub struct CacheResponse<T> {
+    pub cached: Cached<T>,
+    pub rate_limit: Option<RateLimit>,
+    pub revalidation: Option<JoinHandle<Result<Cached<T>, SduiError>>>,
+}
+
+impl<T> CacheResponse<T> {
+    pub fn is_stale(&self) -> bool {
+        self.revalidation.is_some()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TimeTableCache {
+    dir: PathBuf,
+    school_id: u64,
+    user_id: u64,
+    max_age: Duration,
+    runtime: Option<Handle>,
+}
+
+static TEMP_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+impl TimeTableCache {
+    pub fn new(dir: impl Into<PathBuf>, school_id: u64, user_id: u64) -> Self {
+        TimeTableCache {
+            dir: dir.into(),
+            school_id,
+            user_id,
+            max_age: Duration::from_secs(5 * 60),
+            runtime: None,
+        }
+    }
+
+    pub fn max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = max_age;
+        self
+    }
+
+    pub fn runtime(mut self, runtime: Handle) -> Self {
+        self.runtime = Some(runtime);
+        self
+    }
+
+    pub async fn get_timetable(
+        &self,
+        token: &str,
+        target: &TimetableTarget,
+        begin: &Date,
+        end: &Date,
+    ) -> Result<CacheResponse<TimeTable>, SduiError> {
+        let key = format!(
+            "school-{}-user-{}-timetable-{}-{}-{}",
+            self.school_id,
+            self.user_id,
+            target.to_string().replace('/', "-"),
+            begin,
+            end
+        );
+        let token = token.to_owned();
+        let target = target.clone();
+        let (begin, end) = (*begin, *end);
+        self.get(key, async move {
+            get_target_timetable(&token, &target, &begin, &end).await
+        })
+        .await
+    }
+
+    pub async fn get_times(&self, token: &str) -> Result<CacheResponse<Vec<Time>>, SduiError> {
+        let token = token.to_owned();
+        let key = format!("school-{}-times", self.school_id);
+        self.get(key, async move { get_times(&token).await }).await
+    }
+
+    pub async fn prune(&self, max_age: Duration) -> Result<usize, SduiError> {
+        let mut entries = match tokio::fs::read_dir(&self.dir).await {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+            Err(err) => return Err(SduiError::IOError(err)),
+        };
+        let mut pruned = 0;
+        while let Some(entry) = entries.next_entry().await.map_err(SduiError::IOError)? {
+            let path = entry.path();
+            // Leftover .tmp files come from writes that were interrupted.
+            if path
+                .extension()
+                .is_none_or(|extension| extension != "json" && extension != "tmp")
+            {
+                continue;
+            }
+            let modified = entry
+                .metadata()
+                .await
+                .and_then(|metadata| metadata.modified())
+                .map_err(SduiError::IOError)?;
+            if modified.elapsed().unwrap_or_default() > max_age {
+                tokio::fs::remove_file(path)
+                    .await
+                    .map_err(SduiError::IOError)?;
+                pruned += 1;
+            }
+        }
+        Ok(pruned)
+    }
+
+    async fn get<T>(
+        &self,
+        key: String,
+        fetch: impl Future<Output = SduiResult<T>> + Send + 'static,
+    ) -> Result<CacheResponse<T>, SduiError>
+    where
+        T: Serialize + DeserializeOwned + Send + 'static,
+    {
+        match self.read(&key).await {
+            Some(cached) if cached.age() <= self.max_age => Ok(CacheResponse {
+                cached,
+                rate_limit: None,
+                revalidation: None,
+            }),
+            Some(cached) => {
+                // Revalidation needs a tokio runtime; without one the entry is refreshed inline.
+                let Some(runtime) = self.runtime.clone().or_else(|| Handle::try_current().ok())
+                else {
+                    return match fetch.await {
+                        Ok((value, rate_limit)) => Ok(CacheResponse {
+                            cached: self.write(&key, value).await?,
+                            rate_limit: Some(rate_limit),
+                            revalidation: None,
+                        }),
+                        Err(_) => Ok(CacheResponse {
+                            cached,
+                            rate_limit: None,
+                            revalidation: None,
+                        }),
+                    };
+                };
+                let cache = self.clone();
+                let revalidation = runtime.spawn(async move {
+                    let (value, _) = fetch.await?;
+                    cache.write(&key, value).await
+                });
+                Ok(CacheResponse {
+                    cached,
+                    rate_limit: None,
+                    revalidation: Some(revalidation),
+                })
+            }
+            None => {
+                let (value, rate_limit) = fetch.await?;
+                Ok(CacheResponse {
+                    cached: self.write(&key, value).await?,
+                    rate_limit: Some(rate_limit),
+                    revalidation: None,
+                })
+            }
+        }
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", key))
+    }
+
+    async fn read<T: DeserializeOwned>(&self, key: &str) -> Option<Cached<T>> {
+        let data = tokio::fs::read(self.path(key)).await.ok()?;
+        serde_json::from_slice(&data).ok()
+    }
+
+    async fn write<T: Serialize>(&self, key: &str, value: T) -> Result<Cached<T>, SduiError> {
+        let cached = Cached {
+            value,
+            fetched_at: now(),
+        };
+        let data = serde_json::to_vec(&cached).map_err(|_| SduiError::JSONError)?;
+        self.write_data(key, data).await?;
+        Ok(cached)
+    }
+
+    async fn write_data(&self, key: &str, data: Vec<u8>) -> Result<(), SduiError> {
+        tokio::fs::create_dir_all(&self.dir)
+            .await
+            .map_err(SduiError::IOError)?;
+        let temp = self.dir.join(format!(
+            "{}.{}-{}.tmp",
+            key,
+            std::process::id(),
+            TEMP_COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        let result = match tokio::fs::write(&temp, data).await {
+            Ok(()) => tokio::fs::rename(&temp, self.path(key)).await,
+            Err(err) => Err(err),
+        };
+        if result.is_err() {
+            let _ = tokio::fs::remove_file(&temp).await;
+        }
+        result.map_err(SduiError::IOError)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_stale_while_revalidate() {
+        let dir = std::env::temp_dir().join(format!("rust_sdui_cache_{}", std::process::id()));
+        let cache = TimeTableCache::new(&dir, 1, 2);
+        cache.write("times", Vec::<Time>::new()).await.unwrap();
+
+        let response = cache
+            .get::<Vec<Time>>("times".to_owned(), async { Err(SduiError::NotLoggedIn) })
+            .await
+            .unwrap();
+        assert!(!response.is_stale());
+
+        let stale = Cached {
+            value: Vec::<Time>::new(),
+            fetched_at: now() - 3600,
+        };
+        cache
+            .write_data("times", serde_json::to_vec(&stale).unwrap())
+            .await
+            .unwrap();
+        let response = cache
+            .get::<Vec<Time>>("times".to_owned(), async { Err(SduiError::NotLoggedIn) })
+            .await
+            .unwrap();
+        assert!(response.is_stale());
+        assert_eq!(response.cached.fetched_at, stale.fetched_at);
+        assert!(response.revalidation.unwrap().await.unwrap().is_err());
+
+        std::fs::write(dir.join("times.1-0.tmp"), b"{").unwrap();
+        assert_eq!(cache.prune(Duration::ZERO).await.unwrap(), 2);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
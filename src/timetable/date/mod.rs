@@ -0,0 +1,355 @@
+use std::{
+    fmt::Display,
+    str::FromStr,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde::{de::Visitor, Deserialize, Serialize};
+
+use crate::prelude::*;
+
+const DAY: u64 = 86400;
+
+#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq, PartialOrd, Ord)]
+pub struct Date {
+    year: i32,
+    month: u8,
+    day: u8,
+}
+
+pub fn days_in_month(month: u8, year: i32) -> u8 {
+    match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        _ => 0,
+    }
+}
+
+impl Date {
+    pub fn new(day: u8, month: u8, year: i32) -> Option<Self> {
+        (day >= 1 && day <= days_in_month(month, year)).then_some(Date { year, month, day })
+    }
+
+    pub fn today() -> Self {
+        Date::from_timestamp(
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+        )
+    }
+
+    pub fn day(&self) -> u8 {
+        self.day
+    }
+
+    pub fn month(&self) -> u8 {
+        self.month
+    }
+
+    pub fn year(&self) -> i32 {
+        self.year
+    }
+
+    pub fn from_timestamp(timestamp: u64) -> Self {
+        Date::from_days((timestamp / DAY) as i64)
+    }
+
+    pub fn to_timestamp(&self) -> u64 {
+        self.days().max(0) as u64 * DAY
+    }
+
+    fn from_days(days: i64) -> Self {
+        // Days since 1970-01-01 to a civil date, see http://howardhinnant.github.io/date_algorithms.html
+        let days = days + 719468;
+        let era = days.div_euclid(146097);
+        let day_of_era = days.rem_euclid(146097);
+        let year_of_era =
+            (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let month_index = (5 * day_of_year + 2) / 153;
+        let day = (day_of_year - (153 * month_index + 2) / 5 + 1) as u8;
+        let month = if month_index < 10 {
+            month_index + 3
+        } else {
+            month_index - 9
+        } as u8;
+        let year = (year_of_era + era * 400 + i64::from(month <= 2)) as i32;
+        Date { year, month, day }
+    }
+
+    fn days(&self) -> i64 {
+        let year = i64::from(self.year) - i64::from(self.month <= 2);
+        let era = year.div_euclid(400);
+        let year_of_era = year.rem_euclid(400);
+        let month = i64::from(self.month);
+        let day_of_year = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5
+            + i64::from(self.day)
+            - 1;
+        let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+        era * 146097 + day_of_era - 719468
+    }
+
+    pub fn add_days(&self, days: i64) -> Self {
+        Date::from_days(self.days() + days)
+    }
+
+    pub fn days_until(&self, other: &Date) -> i64 {
+        other.days() - self.days()
+    }
+
+    pub fn weekday(&self) -> u8 {
+        // ISO weekday from 1 (monday) to 7 (sunday), 1970-01-01 was a thursday
+        ((self.days() + 3).rem_euclid(7) + 1) as u8
+    }
+
+    pub fn ordinal(&self) -> u16 {
+        Date {
+            year: self.year,
+            month: 1,
+            day: 1,
+        }
+        .days_until(self) as u16
+            + 1
+    }
+
+    pub fn week(&self) -> Week {
+        let thursday = self.add_days(4 - i64::from(self.weekday()));
+        Week {
+            year: thursday.year,
+            week: ((thursday.ordinal() - 1) / 7 + 1) as u8,
+        }
+    }
+
+    pub fn range_to(&self, end: &Date) -> DateRange {
+        DateRange {
+            next: *self,
+            end: *end,
+        }
+    }
+}
+
+impl Display for Date {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+impl FromStr for Date {
+    type Err = SduiError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.splitn(3, '-');
+        let mut next = || parts.next().filter(|part| !part.is_empty());
+        let date = match (next(), next(), next()) {
+            (Some(year), Some(month), Some(day)) => {
+                match (year.parse(), month.parse(), day.parse()) {
+                    (Ok(year), Ok(month), Ok(day)) => Date::new(day, month, year),
+                    _ => None,
+                }
+            }
+            _ => None,
+        };
+        date.ok_or_else(|| SduiError::InvalidDate(s.to_owned()))
+    }
+}
+
+impl Serialize for Date {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for Date {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        struct DateVisitor;
+
+        impl<'de> Visitor<'de> for DateVisitor {
+            type Value = Date;
+
+            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+                formatter.write_str("Expected an ISO date like 2023-05-08")
+            }
+            fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                v.parse().map_err(|_| {
+                    serde::de::Error::invalid_value(serde::de::Unexpected::Str(v), &self)
+                })
+            }
+        }
+        deserializer.deserialize_str(DateVisitor {})
+    }
+}
+
+pub struct DateRange {
+    next: Date,
+    end: Date,
+}
+
+impl Iterator for DateRange {
+    type Item = Date;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.next > self.end {
+            return None;
+        }
+        let date = self.next;
+        self.next = date.add_days(1);
+        Some(date)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq, PartialOrd, Ord)]
+pub struct Week {
+    year: i32,
+    week: u8,
+}
+
+impl Week {
+    pub fn new(year: i32, week: u8) -> Option<Self> {
+        let weeks = Date {
+            year,
+            month: 12,
+            day: 28,
+        }
+        .week()
+        .week;
+        (week >= 1 && week <= weeks).then_some(Week { year, week })
+    }
+
+    pub fn current() -> Self {
+        Date::today().week()
+    }
+
+    pub fn year(&self) -> i32 {
+        self.year
+    }
+
+    pub fn week(&self) -> u8 {
+        self.week
+    }
+
+    pub fn first_day(&self) -> Date {
+        let january_fourth = Date {
+            year: self.year,
+            month: 1,
+            day: 4,
+        };
+        january_fourth
+            .add_days(1 - i64::from(january_fourth.weekday()) + (i64::from(self.week) - 1) * 7)
+    }
+
+    pub fn last_day(&self) -> Date {
+        self.first_day().add_days(6)
+    }
+
+    pub fn days(&self) -> DateRange {
+        self.first_day().range_to(&self.last_day())
+    }
+
+    pub fn next(&self) -> Self {
+        self.first_day().add_days(7).week()
+    }
+
+    pub fn previous(&self) -> Self {
+        self.first_day().add_days(-7).week()
+    }
+}
+
+impl Display for Week {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:04}-W{:02}", self.year, self.week)
+    }
+}
+
+impl FromStr for Week {
+    type Err = SduiError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.split_once("-W")
+            .and_then(|(year, week)| match (year.parse(), week.parse()) {
+                (Ok(year), Ok(week)) => Week::new(year, week),
+                _ => None,
+            })
+            .ok_or_else(|| SduiError::InvalidDate(s.to_owned()))
+    }
+}
+
+impl Serialize for Week {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for Week {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        struct WeekVisitor;
+
+        impl<'de> Visitor<'de> for WeekVisitor {
+            type Value = Week;
+
+            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+                formatter.write_str("Expected an ISO week like 2023-W19")
+            }
+            fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                v.parse().map_err(|_| {
+                    serde::de::Error::invalid_value(serde::de::Unexpected::Str(v), &self)
+                })
+            }
+        }
+        deserializer.deserialize_str(WeekVisitor {})
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_date() {
+        let date: Date = "2024-02-29".parse().unwrap();
+        assert_eq!(date, Date::new(29, 2, 2024).unwrap());
+        assert!(Date::new(29, 2, 2023).is_none());
+        assert!("2023-13-01".parse::<Date>().is_err());
+        assert_eq!(Date::new(8, 5, 2023).unwrap().to_string(), "2023-05-08");
+        assert!(Date::new(1, 2, 2023) > Date::new(31, 1, 2023));
+        assert_eq!(Date::from_timestamp(date.to_timestamp()), date);
+        assert_eq!(date.add_days(1), Date::new(1, 3, 2024).unwrap());
+        assert_eq!(date.weekday(), 4);
+        assert_eq!(date.range_to(&date.add_days(2)).count(), 3);
+    }
+
+    #[test]
+    fn test_week() {
+        let week = Date::new(1, 1, 2021).unwrap().week();
+        assert_eq!((week.year(), week.week()), (2020, 53));
+        assert_eq!(week.first_day(), Date::new(28, 12, 2020).unwrap());
+        assert_eq!(week.next(), Week::new(2021, 1).unwrap());
+        assert!(Week::new(2021, 53).is_none());
+        assert_eq!(week.to_string(), "2020-W53");
+        assert_eq!("2020-W53".parse::<Week>().unwrap(), week);
+        assert_eq!(serde_json::to_string(&week).unwrap(), r#""2020-W53""#);
+        assert!(serde_json::from_str::<Week>(r#""2023-W00""#).is_err());
+        assert!(serde_json::from_str::<Week>(r#""2023-W60""#).is_err());
+        assert!(serde_json::from_str::<Week>(r#"{"year":2023,"week":60}"#).is_err());
+    }
+}
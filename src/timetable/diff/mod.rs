@@ -0,0 +1,80 @@
+use std::collections::BTreeMap;
+
+use crate::timetable::*;
+
+#[derive(Debug, Clone, Hash, Eq, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
+#[allow(clippy::large_enum_variant)]
+pub enum TimeTableChange {
+    Added(Lesson),
+    Removed(Lesson),
+    Changed { old: Lesson, new: Lesson },
+}
+
+impl TimeTableChange {
+    pub fn lesson(&self) -> &Lesson {
+        match self {
+            TimeTableChange::Added(lesson) => lesson,
+            TimeTableChange::Removed(lesson) => lesson,
+            TimeTableChange::Changed { new, .. } => new,
+        }
+    }
+}
+
+impl TimeTable {
+    pub fn diff(&self, newer: &TimeTable) -> Vec<TimeTableChange> {
+        diff(&self.lessons, &newer.lessons)
+    }
+}
+
+pub fn diff(old: &[Lesson], new: &[Lesson]) -> Vec<TimeTableChange> {
+    let old: BTreeMap<u64, &Lesson> = old.iter().map(|lesson| (lesson.id, lesson)).collect();
+    let new: BTreeMap<u64, &Lesson> = new.iter().map(|lesson| (lesson.id, lesson)).collect();
+    let mut changes: Vec<TimeTableChange> = old
+        .iter()
+        .filter(|(id, _)| !new.contains_key(id))
+        .map(|(_, lesson)| TimeTableChange::Removed((*lesson).clone()))
+        .collect();
+    for (id, lesson) in &new {
+        match old.get(id) {
+            None => changes.push(TimeTableChange::Added((*lesson).clone())),
+            Some(old) if old != lesson => changes.push(TimeTableChange::Changed {
+                old: (*old).clone(),
+                new: (*lesson).clone(),
+            }),
+            Some(_) => {}
+        }
+    }
+    changes.sort_by_key(|change| change.lesson().begins_at);
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::timetable::tests::lesson;
+
+    #[test]
+    fn test_diff() {
+        let old = vec![
+            lesson(1, 100, LessonKind::NORMAL),
+            lesson(2, 200, LessonKind::NORMAL),
+            lesson(3, 300, LessonKind::NORMAL),
+        ];
+        let new = vec![
+            lesson(1, 100, LessonKind::NORMAL),
+            lesson(3, 300, LessonKind::CANCLED),
+            lesson(4, 400, LessonKind::ADDITIONAL),
+        ];
+        assert_eq!(
+            diff(&old, &new),
+            vec![
+                TimeTableChange::Removed(lesson(2, 200, LessonKind::NORMAL)),
+                TimeTableChange::Changed {
+                    old: lesson(3, 300, LessonKind::NORMAL),
+                    new: lesson(3, 300, LessonKind::CANCLED),
+                },
+                TimeTableChange::Added(lesson(4, 400, LessonKind::ADDITIONAL)),
+            ]
+        );
+    }
+}
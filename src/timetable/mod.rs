@@ -1,28 +1,168 @@
+use std::{collections::BTreeMap, fmt::Display};
+
 use serde::{de::Visitor, Deserialize, Serialize};
 
 use crate::prelude::*;
 
+mod analytics;
+mod cache;
+mod date;
+mod diff;
+mod schedule;
+mod substitution;
+mod watcher;
+pub use crate::timetable::analytics::*;
+pub use crate::timetable::cache::*;
+pub use crate::timetable::date::*;
+pub use crate::timetable::diff::*;
+pub use crate::timetable::schedule::*;
+pub use crate::timetable::substitution::*;
+pub use crate::timetable::watcher::*;
+
 #[cfg(feature = "processing")]
 mod processing;
 #[cfg(feature = "processing")]
 pub use crate::timetable::processing::*;
 
+const RANGE_CHUNK_DAYS: i64 = 7;
+
 pub async fn get_timetable(
     token: &str,
     user_id: &str,
     begin: &Date,
     end: &Date,
+) -> SduiResult<TimeTable> {
+    get_target_timetable(token, &TimetableTarget::user(user_id)?, begin, end).await
+}
+
+pub async fn get_target_timetable(
+    token: &str,
+    target: &TimetableTarget,
+    begin: &Date,
+    end: &Date,
 ) -> SduiResult<TimeTable> {
     request(
-        &format!("https://api.sdui.app/v1/timetables/users/{}/timetable?begins_at={}-{}-{}&ends_at={}-{}-{}",user_id,begin.year,begin.month,begin.day,end.year,end.month,end.day),
-        token
-    ).await
+        &format!(
+            "https://api.sdui.app/v1/timetables/{}/timetable?begins_at={}&ends_at={}",
+            target, begin, end
+        ),
+        token,
+    )
+    .await
+}
+
+pub async fn get_week_timetable(
+    token: &str,
+    target: &TimetableTarget,
+    week: &Week,
+) -> SduiResult<TimeTable> {
+    get_target_timetable(token, target, &week.first_day(), &week.last_day()).await
+}
+
+pub async fn get_timetable_range(
+    token: &str,
+    target: &TimetableTarget,
+    begin: &Date,
+    end: &Date,
+) -> SduiResult<TimeTable> {
+    let mut start = *begin;
+    let mut lessons: BTreeMap<u64, Lesson> = BTreeMap::new();
+    let mut last_updated_at = String::new();
+    let mut rate_limit: Option<RateLimit> = None;
+    while start <= *end {
+        let chunk_end = start.add_days(RANGE_CHUNK_DAYS - 1).min(*end);
+        let (timetable, chunk_rate_limit) =
+            get_target_timetable(token, target, &start, &chunk_end).await?;
+        rate_limit = Some(match rate_limit {
+            Some(rate_limit) => rate_limit.join(chunk_rate_limit),
+            None => chunk_rate_limit,
+        });
+        last_updated_at = last_updated_at.max(timetable.last_updated_at);
+        lessons.extend(
+            timetable
+                .lessons
+                .into_iter()
+                .map(|lesson| (lesson.id, lesson)),
+        );
+        start = chunk_end.add_days(1);
+    }
+    let mut lessons: Vec<Lesson> = lessons.into_values().collect();
+    lessons.sort_by_key(|lesson| (lesson.begins_at, lesson.id));
+    Ok((
+        TimeTable {
+            lessons,
+            last_updated_at,
+        },
+        rate_limit.unwrap_or(RateLimit {
+            limit: 0,
+            remaining: 0,
+        }),
+    ))
+}
+
+pub async fn get_grades(token: &str) -> SduiResult<Vec<Grade>> {
+    request("https://api.sdui.app/v1/timetables/grades", token).await
+}
+
+pub async fn get_teachers(token: &str) -> SduiResult<Vec<Teacher>> {
+    request("https://api.sdui.app/v1/timetables/teachers", token).await
+}
+
+pub async fn get_bookables(token: &str) -> SduiResult<Vec<Bookable>> {
+    request("https://api.sdui.app/v1/timetables/bookables", token).await
 }
 
 pub async fn get_times(token: &str) -> SduiResult<Vec<Time>> {
     request("https://api.sdui.app/v1/timetables/times", token).await
 }
 
+#[derive(Debug, Clone, Hash, Eq, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum TimetableTarget {
+    User(u64),
+    Grade(u64),
+    Teacher(u64),
+    Bookable(u64),
+}
+
+impl TimetableTarget {
+    pub(crate) fn user(user_id: &str) -> Result<Self, SduiError> {
+        user_id
+            .trim()
+            .parse()
+            .map(TimetableTarget::User)
+            .map_err(|_| SduiError::InvalidTarget(user_id.to_owned()))
+    }
+}
+
+impl Display for TimetableTarget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TimetableTarget::User(id) => write!(f, "users/{}", id),
+            TimetableTarget::Grade(id) => write!(f, "grades/{}", id),
+            TimetableTarget::Teacher(id) => write!(f, "teachers/{}", id),
+            TimetableTarget::Bookable(id) => write!(f, "bookables/{}", id),
+        }
+    }
+}
+
+impl From<&Grade> for TimetableTarget {
+    fn from(grade: &Grade) -> Self {
+        TimetableTarget::Grade(grade.id)
+    }
+}
+
+impl From<&Teacher> for TimetableTarget {
+    fn from(teacher: &Teacher) -> Self {
+        TimetableTarget::Teacher(teacher.id)
+    }
+}
+
+impl From<&Bookable> for TimetableTarget {
+    fn from(bookable: &Bookable) -> Self {
+        TimetableTarget::Bookable(bookable.id)
+    }
+}
+
 #[derive(Debug, Clone, Hash, Eq, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct Time {
     pub begins_at: u64,
@@ -41,16 +181,35 @@ pub struct Time {
 pub enum TimeKind {
     BREAK,
     LESSON,
+    Other(String),
 }
+
+impl TimeKind {
+    pub fn as_str(&self) -> &str {
+        match self {
+            TimeKind::BREAK => "BREAK",
+            TimeKind::LESSON => "LESSON",
+            TimeKind::Other(kind) => kind,
+        }
+    }
+}
+
+impl From<&str> for TimeKind {
+    fn from(kind: &str) -> Self {
+        match kind.to_ascii_uppercase().as_str() {
+            "BREAK" => TimeKind::BREAK,
+            "LESSON" => TimeKind::LESSON,
+            _ => TimeKind::Other(kind.to_owned()),
+        }
+    }
+}
+
 impl Serialize for TimeKind {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: serde::Serializer,
     {
-        match self {
-            TimeKind::BREAK => serializer.serialize_str("BREAK"),
-            TimeKind::LESSON => serializer.serialize_str("LESSON"),
-        }
+        serializer.serialize_str(self.as_str())
     }
 }
 
@@ -65,20 +224,13 @@ impl<'de> Deserialize<'de> for TimeKind {
             type Value = TimeKind;
 
             fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
-                formatter.write_str("Expected BREAK,LESSON")
+                formatter.write_str("Expected a time kind like BREAK or LESSON")
             }
             fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
             where
                 E: serde::de::Error,
             {
-                match v {
-                    "BREAK" => Ok(TimeKind::BREAK),
-                    "LESSON" => Ok(TimeKind::LESSON),
-                    _ => Err(serde::de::Error::invalid_value(
-                        serde::de::Unexpected::Str(v),
-                        &self,
-                    )),
-                }
+                Ok(TimeKind::from(v))
             }
         }
         deserializer.deserialize_str(TimeKindVisitor {})
@@ -90,19 +242,6 @@ pub struct TimeMeta {
     pub displayname: String,
 }
 
-#[derive(Debug, Clone, Hash, Eq, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
-pub struct Date {
-    pub day: u8,
-    pub month: u8,
-    pub year: i32,
-}
-
-impl Date {
-    pub fn new(day: u8, month: u8, year: i32) -> Self {
-        Date { day, month, year }
-    }
-}
-
 #[derive(Debug, Clone, Hash, Eq, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct TimeTable {
     pub lessons: Vec<Lesson>,
@@ -120,6 +259,7 @@ pub struct Lesson {
     pub comment: String,
     pub course: Course,
     pub meta: LessonMeta,
+    #[serde(default)]
     pub kind: LessonKind,
 }
 
@@ -186,12 +326,38 @@ pub struct SubjectMeta {
     pub displayname: String,
 }
 
-#[derive(Debug, Clone, Hash, Eq, PartialEq, PartialOrd, Ord)]
+#[derive(Debug, Clone, Default, Hash, Eq, PartialEq, PartialOrd, Ord)]
 pub enum LessonKind {
+    #[default]
     NORMAL,
     SUBSTITUTION,
     CANCLED,
     ADDITIONAL,
+    Other(String),
+}
+
+impl LessonKind {
+    pub fn as_str(&self) -> &str {
+        match self {
+            LessonKind::NORMAL => "NORMAL",
+            LessonKind::SUBSTITUTION => "SUBSTITUTION",
+            LessonKind::CANCLED => "CANCLED",
+            LessonKind::ADDITIONAL => "ADDITIONAL",
+            LessonKind::Other(kind) => kind,
+        }
+    }
+}
+
+impl From<&str> for LessonKind {
+    fn from(kind: &str) -> Self {
+        match kind.to_ascii_uppercase().as_str() {
+            "" | "NORMAL" => LessonKind::NORMAL,
+            "SUBSTITUTION" => LessonKind::SUBSTITUTION,
+            "CANCLED" | "CANCELED" | "CANCELLED" => LessonKind::CANCLED,
+            "ADDITIONAL" => LessonKind::ADDITIONAL,
+            _ => LessonKind::Other(kind.to_owned()),
+        }
+    }
 }
 
 impl Serialize for LessonKind {
@@ -199,14 +365,10 @@ impl Serialize for LessonKind {
     where
         S: serde::Serializer,
     {
-        match self {
-            LessonKind::NORMAL => serializer.serialize_none(),
-            LessonKind::SUBSTITUTION => serializer.serialize_str("SUBSTITUTION"),
-            LessonKind::CANCLED => serializer.serialize_str("CANCLED"),
-            LessonKind::ADDITIONAL => serializer.serialize_str("ADDITIONAL"),
-        }
+        serializer.serialize_str(self.as_str())
     }
 }
+
 impl<'de> Deserialize<'de> for LessonKind {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
@@ -218,7 +380,7 @@ impl<'de> Deserialize<'de> for LessonKind {
             type Value = LessonKind;
 
             fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
-                formatter.write_str("Expected none,SUBSTITUTION,CANCLED or ADDITIONAL")
+                formatter.write_str("Expected none or a lesson kind like SUBSTITUTION")
             }
             fn visit_none<E>(self) -> Result<Self::Value, E>
             where
@@ -226,36 +388,108 @@ impl<'de> Deserialize<'de> for LessonKind {
             {
                 Ok(LessonKind::NORMAL)
             }
-            fn visit_some<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+            fn visit_unit<E>(self) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                Ok(LessonKind::NORMAL)
+            }
+            fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
             where
-                D: serde::Deserializer<'de>,
+                E: serde::de::Error,
             {
-                struct LessonKindSomeVisitor;
-
-                impl<'de> Visitor<'de> for LessonKindSomeVisitor {
-                    type Value = LessonKind;
-
-                    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
-                        formatter.write_str("Expected none,SUBSTITUTION,CANCLED or ADDITIONAL")
-                    }
-                    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
-                    where
-                        E: serde::de::Error,
-                    {
-                        match v {
-                            "SUBSTITUTION" => Ok(LessonKind::SUBSTITUTION),
-                            "CANCLED" => Ok(LessonKind::CANCLED),
-                            "ADDITIONAL" => Ok(LessonKind::ADDITIONAL),
-                            _ => Err(serde::de::Error::invalid_value(
-                                serde::de::Unexpected::Str(v),
-                                &self,
-                            )),
-                        }
-                    }
-                }
-                deserializer.deserialize_str(LessonKindSomeVisitor)
+                Ok(LessonKind::from(v))
             }
         }
-        deserializer.deserialize_option(LessonKindVisitor {})
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_any(LessonKindVisitor {})
+        } else {
+            deserializer.deserialize_str(LessonKindVisitor {})
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    pub(crate) fn lesson(id: u64, begins_at: u64, kind: LessonKind) -> Lesson {
+        Lesson {
+            bookables: vec![],
+            grades: vec![],
+            teachers: vec![],
+            id,
+            begins_at,
+            ends_at: begins_at + 2700,
+            comment: String::new(),
+            course: Course {
+                meta: CourseMeta {
+                    displayname: "Mathe".to_owned(),
+                    shortname: "M".to_owned(),
+                    color: "#ff0000".to_owned(),
+                    name: "Mathe".to_owned(),
+                    description: String::new(),
+                },
+                subject: Subject {
+                    color: "#ff0000".to_owned(),
+                    meta: SubjectMeta {
+                        displayname: "Mathe".to_owned(),
+                    },
+                    id: 1,
+                    shortcut: "M".to_owned(),
+                    name: "Mathe".to_owned(),
+                },
+                id: 1,
+                name: "Mathe".to_owned(),
+                description: None,
+                subject_id: 1,
+            },
+            meta: LessonMeta {
+                displayname_hour: "1".to_owned(),
+                moved_comment: String::new(),
+                displayname: "Mathe".to_owned(),
+                shortname: "M".to_owned(),
+                displayname_kind: String::new(),
+            },
+            kind,
+        }
+    }
+
+    #[test]
+    fn test_timetable_target() {
+        assert_eq!(TimetableTarget::user("42").unwrap().to_string(), "users/42");
+        assert!(matches!(
+            TimetableTarget::user("self"),
+            Err(SduiError::InvalidTarget(_))
+        ));
+    }
+
+    #[test]
+    fn test_lesson_kind() {
+        let kinds: Vec<LessonKind> =
+            serde_json::from_str(r#"[null, "CANCLED", "CANCELLED", "EXAM"]"#).unwrap();
+        assert_eq!(
+            kinds,
+            vec![
+                LessonKind::NORMAL,
+                LessonKind::CANCLED,
+                LessonKind::CANCLED,
+                LessonKind::Other("EXAM".to_owned()),
+            ]
+        );
+        for kind in kinds {
+            let value = serde_json::to_value(&kind).unwrap();
+            assert_eq!(serde_json::from_value::<LessonKind>(value).unwrap(), kind);
+        }
+        let mut value = serde_json::to_value(lesson(1, 0, LessonKind::CANCLED)).unwrap();
+        value.as_object_mut().unwrap().remove("kind");
+        assert_eq!(
+            serde_json::from_value::<Lesson>(value).unwrap().kind,
+            LessonKind::NORMAL
+        );
+        assert_eq!(
+            serde_json::from_str::<TimeKind>(r#""LUNCH""#).unwrap(),
+            TimeKind::Other("LUNCH".to_owned())
+        );
     }
 }
@@ -1,43 +1,218 @@
-use std::collections::HashMap;
-
 use crate::timetable::*;
 use itertools::Itertools;
+
+mod render;
+pub use crate::timetable::processing::render::*;
+
 pub async fn get_processed_timetable(
     token: &str,
     user_id: &str,
     begin: &Date,
     end: &Date,
 ) -> SduiResult<ProcessedTimeTable> {
-    let timetable = get_timetable(token, user_id, begin, end).await?;
-    let mut days: Vec<(u64, Lesson)> = vec![];
-    for lesson in timetable.0.lessons {
-        let day = lesson.begins_at / 86400;
-        days.push((day, lesson));
-    }
-    let mut processed_time_table: ProcessedTimeTable = ProcessedTimeTable { days: vec![] };
-    days.sort_by_key(|day| day.0);
-    for (_, values) in &days.into_iter().group_by(|day| day.0) {
-        let mut day = Day {
-            times: HashMap::default(),
-        };
-        values
-            .group_by(|v| v.1.meta.displayname_hour.parse().unwrap())
-            .into_iter()
-            .sorted_by_key(|v| v.0)
-            .for_each(|(key, value)| {
-                day.times
-                    .insert(key, value.map(|(_, value)| value).collect_vec());
-            });
-        processed_time_table.days.push(day);
-    }
-    return Ok((processed_time_table, timetable.1));
+    get_processed_target_timetable(token, &TimetableTarget::user(user_id)?, begin, end).await
+}
+
+pub async fn get_processed_target_timetable(
+    token: &str,
+    target: &TimetableTarget,
+    begin: &Date,
+    end: &Date,
+) -> SduiResult<ProcessedTimeTable> {
+    let (timetable, rate_limit) = get_target_timetable(token, target, begin, end).await?;
+    Ok((
+        ProcessedTimeTable::from_lessons(&timetable.lessons),
+        rate_limit,
+    ))
 }
 
 #[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
 pub struct ProcessedTimeTable {
     pub days: Vec<Day>,
 }
+
 #[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
 pub struct Day {
-    pub times: HashMap<u8, Vec<Lesson>>,
+    pub date: Date,
+    pub slots: Vec<Slot>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Slot {
+    pub first_hour: u8,
+    pub last_hour: u8,
+    pub blocks: Vec<Block>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Block {
+    pub first_hour: u8,
+    pub last_hour: u8,
+    pub lessons: Vec<Lesson>,
+}
+
+fn hour(lesson: &Lesson) -> u8 {
+    lesson.meta.displayname_hour.parse().unwrap_or_default()
+}
+
+impl Block {
+    pub fn course(&self) -> &Course {
+        &self.lessons[0].course
+    }
+
+    pub fn kind(&self) -> &LessonKind {
+        &self.lessons[0].kind
+    }
+
+    pub fn begins_at(&self) -> u64 {
+        self.lessons[0].begins_at
+    }
+
+    pub fn ends_at(&self) -> u64 {
+        self.lessons[self.lessons.len() - 1].ends_at
+    }
+
+    pub fn lesson_at(&self, hour: u8) -> Option<&Lesson> {
+        self.lessons
+            .get(usize::from(hour.checked_sub(self.first_hour)?))
+    }
+
+    fn continues_with(&self, lesson: &Lesson) -> bool {
+        let last = &self.lessons[self.lessons.len() - 1];
+        self.last_hour + 1 == hour(lesson)
+            && last.course.id == lesson.course.id
+            && last.kind == lesson.kind
+            && last.teachers == lesson.teachers
+            && last.bookables == lesson.bookables
+    }
+}
+
+impl Day {
+    pub fn lessons_at(&self, hour: u8) -> Vec<&Lesson> {
+        self.slots
+            .iter()
+            .flat_map(|slot| &slot.blocks)
+            .filter_map(|block| block.lesson_at(hour))
+            .collect()
+    }
+
+    fn from_lessons(date: Date, lessons: Vec<Lesson>) -> Self {
+        let mut blocks: Vec<Block> = vec![];
+        for lesson in lessons
+            .into_iter()
+            .sorted_by_key(|lesson| (hour(lesson), lesson.course.id, lesson.id))
+        {
+            match blocks
+                .iter_mut()
+                .find(|block| block.continues_with(&lesson))
+            {
+                Some(block) => {
+                    block.last_hour += 1;
+                    block.lessons.push(lesson);
+                }
+                None => blocks.push(Block {
+                    first_hour: hour(&lesson),
+                    last_hour: hour(&lesson),
+                    lessons: vec![lesson],
+                }),
+            }
+        }
+        let mut slots: Vec<Slot> = vec![];
+        for block in blocks
+            .into_iter()
+            .sorted_by_key(|block| (block.first_hour, block.course().id))
+        {
+            match slots.last_mut() {
+                Some(slot) if block.first_hour <= slot.last_hour => {
+                    slot.last_hour = slot.last_hour.max(block.last_hour);
+                    slot.blocks.push(block);
+                }
+                _ => slots.push(Slot {
+                    first_hour: block.first_hour,
+                    last_hour: block.last_hour,
+                    blocks: vec![block],
+                }),
+            }
+        }
+        Day { date, slots }
+    }
+}
+
+impl ProcessedTimeTable {
+    pub fn from_lessons(lessons: &[Lesson]) -> Self {
+        ProcessedTimeTable {
+            days: lessons
+                .iter()
+                .cloned()
+                .into_group_map_by(|lesson| Date::from_timestamp(lesson.begins_at))
+                .into_iter()
+                .sorted_by_key(|(date, _)| *date)
+                .map(|(date, lessons)| Day::from_lessons(date, lessons))
+                .collect(),
+        }
+    }
+
+    pub fn lessons(&self) -> impl Iterator<Item = &Lesson> {
+        self.days
+            .iter()
+            .flat_map(|day| &day.slots)
+            .flat_map(|slot| &slot.blocks)
+            .flat_map(|block| &block.lessons)
+    }
+
+    pub fn courses(&self) -> Vec<&Course> {
+        self.lessons()
+            .map(|lesson| &lesson.course)
+            .unique_by(|course| course.id)
+            .sorted_by_key(|course| &course.name)
+            .collect()
+    }
+
+    pub fn filter_courses(&self, course_ids: &[u64]) -> Self {
+        let lessons: Vec<Lesson> = self
+            .lessons()
+            .filter(|lesson| course_ids.contains(&lesson.course.id))
+            .cloned()
+            .collect();
+        ProcessedTimeTable::from_lessons(&lessons)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::timetable::tests::lesson;
+
+    fn lesson_at(id: u64, hour: u8, course_id: u64) -> Lesson {
+        let mut lesson = lesson(id, 1683504000 + u64::from(hour) * 3600, LessonKind::NORMAL);
+        lesson.meta.displayname_hour = hour.to_string();
+        lesson.course.id = course_id;
+        lesson
+    }
+
+    #[test]
+    fn test_blocks_and_slots() {
+        let lessons = [
+            lesson_at(1, 2, 1),
+            lesson_at(2, 1, 1),
+            lesson_at(3, 1, 2),
+            lesson_at(4, 2, 2),
+            lesson_at(5, 3, 3),
+            lesson_at(6, 4, 1),
+        ];
+        let timetable = ProcessedTimeTable::from_lessons(&lessons);
+        let day = &timetable.days[0];
+        assert_eq!(day.date, Date::new(8, 5, 2023).unwrap());
+        let slots: Vec<(u8, u8, usize)> = day
+            .slots
+            .iter()
+            .map(|slot| (slot.first_hour, slot.last_hour, slot.blocks.len()))
+            .collect();
+        assert_eq!(slots, vec![(1, 2, 2), (3, 3, 1), (4, 4, 1)]);
+        assert_eq!(day.slots[0].blocks[0].lessons.len(), 2);
+
+        let filtered = timetable.filter_courses(&[2, 3]);
+        assert_eq!(filtered.lessons().count(), 3);
+        assert_eq!(filtered.days[0].slots[0].blocks.len(), 1);
+    }
 }
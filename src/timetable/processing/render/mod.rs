@@ -0,0 +1,357 @@
+use std::collections::BTreeSet;
+
+use crate::timetable::*;
+
+const WEEKDAYS: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+
+#[derive(Debug, Clone, Hash, Eq, PartialEq, PartialOrd, Ord)]
+pub struct RenderOptions {
+    teachers: bool,
+    rooms: bool,
+    highlight_changes: bool,
+    colors: bool,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        RenderOptions {
+            teachers: true,
+            rooms: true,
+            highlight_changes: true,
+            colors: false,
+        }
+    }
+}
+
+impl RenderOptions {
+    pub fn new() -> Self {
+        RenderOptions::default()
+    }
+
+    pub fn teachers(mut self, teachers: bool) -> Self {
+        self.teachers = teachers;
+        self
+    }
+
+    pub fn rooms(mut self, rooms: bool) -> Self {
+        self.rooms = rooms;
+        self
+    }
+
+    pub fn highlight_changes(mut self, highlight_changes: bool) -> Self {
+        self.highlight_changes = highlight_changes;
+        self
+    }
+
+    pub fn colors(mut self, colors: bool) -> Self {
+        self.colors = colors;
+        self
+    }
+}
+
+struct Grid<'a> {
+    days: Vec<Date>,
+    rows: Vec<(u8, Vec<Vec<&'a Lesson>>)>,
+}
+
+impl ProcessedTimeTable {
+    fn grid(&self) -> Grid<'_> {
+        let hours: BTreeSet<u8> = self
+            .days
+            .iter()
+            .flat_map(|day| &day.slots)
+            .flat_map(|slot| slot.first_hour..=slot.last_hour)
+            .collect();
+        Grid {
+            days: self.days.iter().map(|day| day.date).collect(),
+            rows: hours
+                .into_iter()
+                .map(|hour| {
+                    (
+                        hour,
+                        self.days.iter().map(|day| day.lessons_at(hour)).collect(),
+                    )
+                })
+                .collect(),
+        }
+    }
+
+    pub fn render_terminal(&self, options: &RenderOptions) -> String {
+        let grid = self.grid();
+        let header: Vec<Vec<(String, String)>> = std::iter::once(vec![])
+            .chain(
+                grid.days
+                    .iter()
+                    .map(|date| vec![(day_label(date), String::new())]),
+            )
+            .collect();
+        let rows: Vec<Vec<Vec<(String, String)>>> = grid
+            .rows
+            .iter()
+            .map(|(hour, cells)| {
+                std::iter::once(vec![(hour.to_string(), String::new())])
+                    .chain(cells.iter().map(|lessons| {
+                        lessons
+                            .iter()
+                            .flat_map(|lesson| {
+                                let style = terminal_style(lesson, options);
+                                let marker = if options.highlight_changes && !options.colors {
+                                    change_marker(&lesson.kind)
+                                } else {
+                                    ""
+                                };
+                                lesson_lines(lesson, options).into_iter().enumerate().map(
+                                    move |(index, line)| {
+                                        let line = if index == 0 {
+                                            format!("{}{}", marker, line)
+                                        } else {
+                                            line
+                                        };
+                                        (line, style.clone())
+                                    },
+                                )
+                            })
+                            .collect()
+                    }))
+                    .collect()
+            })
+            .collect();
+        let mut widths = vec![0; grid.days.len() + 1];
+        for row in std::iter::once(&header).chain(&rows) {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                for (line, _) in cell {
+                    *width = (*width).max(line.chars().count());
+                }
+            }
+        }
+        let border = |left: &str, middle: &str, right: &str| {
+            let line = widths
+                .iter()
+                .map(|width| "─".repeat(width + 2))
+                .collect::<Vec<_>>()
+                .join(middle);
+            format!("{}{}{}\n", left, line, right)
+        };
+        let mut text = border("┌", "┬", "┐");
+        for (index, row) in std::iter::once(&header).chain(&rows).enumerate() {
+            if index > 0 {
+                text.push_str(&border("├", "┼", "┤"));
+            }
+            let height = row.iter().map(Vec::len).max().unwrap_or(0).max(1);
+            for line in 0..height {
+                text.push('│');
+                for (cell, width) in row.iter().zip(&widths) {
+                    let (content, style) = cell
+                        .get(line)
+                        .map(|(content, style)| (content.as_str(), style.as_str()))
+                        .unwrap_or(("", ""));
+                    let padding = " ".repeat(width - content.chars().count());
+                    if style.is_empty() {
+                        text.push_str(&format!(" {}{} │", content, padding));
+                    } else {
+                        text.push_str(&format!(" {}{}\x1b[0m{} │", style, content, padding));
+                    }
+                }
+                text.push('\n');
+            }
+        }
+        text.push_str(&border("└", "┴", "┘"));
+        text
+    }
+
+    pub fn render_html(&self, options: &RenderOptions) -> String {
+        let grid = self.grid();
+        let mut html = String::from(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Timetable</title>\n<style>\nbody { font-family: sans-serif; }\ntable { border-collapse: collapse; }\nth, td { border: 1px solid #ccc; padding: 0.3em 0.6em; vertical-align: top; }\n.lesson { border-left: 0.4em solid transparent; padding-left: 0.3em; margin-bottom: 0.2em; }\n.lesson span { display: block; }\n.details { color: #555; font-size: 0.9em; }\n.cancelled { text-decoration: line-through; color: #a00; }\n.substitution, .additional { font-weight: bold; color: #05a; }\n</style>\n</head>\n<body>\n<table>\n<tr><th></th>",
+        );
+        for date in &grid.days {
+            html.push_str(&format!("<th>{}</th>", escape_html(&day_label(date))));
+        }
+        html.push_str("</tr>\n");
+        for (hour, cells) in &grid.rows {
+            html.push_str(&format!("<tr><th>{}</th>", hour));
+            for lessons in cells {
+                html.push_str("<td>");
+                for lesson in lessons {
+                    let class = if options.highlight_changes {
+                        change_class(&lesson.kind)
+                    } else {
+                        ""
+                    };
+                    let color = if options.colors {
+                        parse_color(lesson_color(lesson))
+                            .map(|(r, g, b)| {
+                                format!(" style=\"border-left-color: rgb({}, {}, {})\"", r, g, b)
+                            })
+                            .unwrap_or_default()
+                    } else {
+                        String::new()
+                    };
+                    html.push_str(&format!(
+                        "<div class=\"{}\"{}>",
+                        format!("lesson {}", class).trim_end(),
+                        color
+                    ));
+                    for (index, line) in lesson_lines(lesson, options).iter().enumerate() {
+                        html.push_str(&format!(
+                            "<span{}>{}</span>",
+                            if index > 0 { " class=\"details\"" } else { "" },
+                            escape_html(line)
+                        ));
+                    }
+                    html.push_str("</div>");
+                }
+                html.push_str("</td>");
+            }
+            html.push_str("</tr>\n");
+        }
+        html.push_str("</table>\n</body>\n</html>\n");
+        html
+    }
+
+    pub fn render_markdown(&self, options: &RenderOptions) -> String {
+        let grid = self.grid();
+        let mut markdown = String::from("|   |");
+        for date in &grid.days {
+            markdown.push_str(&format!(" {} |", day_label(date)));
+        }
+        markdown.push_str("\n|---|");
+        markdown.push_str(&"---|".repeat(grid.days.len()));
+        markdown.push('\n');
+        for (hour, cells) in &grid.rows {
+            markdown.push_str(&format!("| {} |", hour));
+            for lessons in cells {
+                let cell = lessons
+                    .iter()
+                    .map(|lesson| {
+                        let text = lesson_lines(lesson, options)
+                            .iter()
+                            .map(|line| escape_markdown(line))
+                            .collect::<Vec<_>>()
+                            .join(" ");
+                        match (&lesson.kind, options.highlight_changes) {
+                            (_, false) | (LessonKind::NORMAL, _) => text,
+                            (LessonKind::CANCLED, _) => format!("~~{}~~", text),
+                            _ => format!("**{}**", text),
+                        }
+                    })
+                    .collect::<Vec<_>>()
+                    .join("<br>");
+                markdown.push_str(&format!(" {} |", cell));
+            }
+            markdown.push('\n');
+        }
+        markdown
+    }
+}
+
+fn day_label(date: &Date) -> String {
+    format!("{} {}", WEEKDAYS[usize::from(date.weekday() - 1)], date)
+}
+
+fn lesson_lines(lesson: &Lesson, options: &RenderOptions) -> Vec<String> {
+    let mut lines = vec![lesson.course.meta.shortname.clone()];
+    if options.teachers && !lesson.teachers.is_empty() {
+        lines.push(
+            lesson
+                .teachers
+                .iter()
+                .map(|teacher| teacher.shortcut.as_str())
+                .collect::<Vec<_>>()
+                .join(", "),
+        );
+    }
+    if options.rooms && !lesson.bookables.is_empty() {
+        lines.push(
+            lesson
+                .bookables
+                .iter()
+                .map(|room| room.shortcut.as_str())
+                .collect::<Vec<_>>()
+                .join(", "),
+        );
+    }
+    lines
+}
+
+fn lesson_color(lesson: &Lesson) -> &str {
+    if lesson.course.meta.color.is_empty() {
+        &lesson.course.subject.color
+    } else {
+        &lesson.course.meta.color
+    }
+}
+
+fn parse_color(color: &str) -> Option<(u8, u8, u8)> {
+    let color = color.strip_prefix('#').unwrap_or(color);
+    if color.len() != 6 {
+        return None;
+    }
+    let channel = |index: usize| u8::from_str_radix(color.get(index..index + 2)?, 16).ok();
+    Some((channel(0)?, channel(2)?, channel(4)?))
+}
+
+fn change_marker(kind: &LessonKind) -> &'static str {
+    match kind {
+        LessonKind::NORMAL => "",
+        LessonKind::CANCLED => "✗ ",
+        LessonKind::SUBSTITUTION => "↻ ",
+        LessonKind::ADDITIONAL => "+ ",
+        LessonKind::Other(_) => "• ",
+    }
+}
+
+fn change_class(kind: &LessonKind) -> &'static str {
+    match kind {
+        LessonKind::NORMAL => "",
+        LessonKind::CANCLED => "cancelled",
+        LessonKind::SUBSTITUTION => "substitution",
+        LessonKind::ADDITIONAL => "additional",
+        LessonKind::Other(_) => "other",
+    }
+}
+
+fn terminal_style(lesson: &Lesson, options: &RenderOptions) -> String {
+    if !options.colors {
+        return String::new();
+    }
+    let mut style = parse_color(lesson_color(lesson))
+        .map(|(r, g, b)| format!("\x1b[38;2;{};{};{}m", r, g, b))
+        .unwrap_or_default();
+    if options.highlight_changes {
+        match lesson.kind {
+            LessonKind::NORMAL => {}
+            LessonKind::CANCLED => style.push_str("\x1b[9m"),
+            _ => style.push_str("\x1b[1m"),
+        }
+    }
+    style
+}
+
+fn escape_markdown(text: &str) -> String {
+    text.replace('|', "\\|")
+        .replace('*', "\\*")
+        .replace('~', "\\~")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::timetable::tests::lesson;
+
+    #[test]
+    fn test_render_markdown() {
+        let mut normal = lesson(1, 1683504000 + 28800, LessonKind::NORMAL);
+        normal.teachers = vec![Teacher {
+            id: 1,
+            name: "Müller".to_owned(),
+            shortcut: "MUE".to_owned(),
+        }];
+        let cancelled = lesson(2, 1683590400 + 28800, LessonKind::CANCLED);
+        let timetable = ProcessedTimeTable::from_lessons(&[normal, cancelled]);
+        assert_eq!(
+            timetable.render_markdown(&RenderOptions::new()),
+            "|   | Mon 2023-05-08 | Tue 2023-05-09 |\n|---|---|---|\n| 1 | M MUE | ~~M~~ |\n"
+        );
+    }
+}
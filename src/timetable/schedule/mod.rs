@@ -0,0 +1,277 @@
+use crate::{
+    calendar::{get_calendar_events, CalendarEvent, CalendarEventKind},
+    timetable::*,
+};
+
+pub async fn get_holidays(
+    token: &str,
+    calendar_id: u64,
+    begin: &Date,
+    end: &Date,
+) -> SduiResult<Vec<Holiday>> {
+    let (events, rate_limit) = get_calendar_events(token, calendar_id, begin, end).await?;
+    Ok((
+        events
+            .iter()
+            .filter(|event| event.kind == CalendarEventKind::HOLIDAY)
+            .map(Holiday::from)
+            .collect(),
+        rate_limit,
+    ))
+}
+
+pub struct ScheduleRequest {
+    token: String,
+    target: TimetableTarget,
+    begin: Date,
+    end: Date,
+    calendars: Vec<u64>,
+    holiday_calendars: Vec<u64>,
+    holidays: Vec<Holiday>,
+}
+
+impl ScheduleRequest {
+    pub fn new(token: &str, target: TimetableTarget, begin: &Date, end: &Date) -> Self {
+        ScheduleRequest {
+            token: token.to_owned(),
+            target,
+            begin: *begin,
+            end: *end,
+            calendars: vec![],
+            holiday_calendars: vec![],
+            holidays: vec![],
+        }
+    }
+
+    pub fn calendar(mut self, calendar_id: u64) -> Self {
+        self.calendars.push(calendar_id);
+        self
+    }
+
+    pub fn holiday_calendar(mut self, calendar_id: u64) -> Self {
+        self.holiday_calendars.push(calendar_id);
+        self
+    }
+
+    pub fn holiday(mut self, holiday: Holiday) -> Self {
+        self.holidays.push(holiday);
+        self
+    }
+
+    pub fn holidays(mut self, holidays: Vec<Holiday>) -> Self {
+        self.holidays.extend(holidays);
+        self
+    }
+
+    pub async fn request(&self) -> SduiResult<Schedule> {
+        let (timetable, mut rate_limit) =
+            get_timetable_range(&self.token, &self.target, &self.begin, &self.end).await?;
+        let mut events = vec![];
+        for calendar_id in &self.calendars {
+            let (calendar_events, calendar_rate_limit) =
+                get_calendar_events(&self.token, *calendar_id, &self.begin, &self.end).await?;
+            rate_limit = rate_limit.join(calendar_rate_limit);
+            events.extend(calendar_events);
+        }
+        let mut holidays = self.holidays.clone();
+        for calendar_id in &self.holiday_calendars {
+            let (calendar_holidays, calendar_rate_limit) =
+                get_holidays(&self.token, *calendar_id, &self.begin, &self.end).await?;
+            rate_limit = rate_limit.join(calendar_rate_limit);
+            holidays.extend(calendar_holidays);
+        }
+        Ok((
+            Schedule::new(
+                &timetable.lessons,
+                &events,
+                &holidays,
+                &self.begin,
+                &self.end,
+            ),
+            rate_limit,
+        ))
+    }
+}
+
+#[derive(Debug, Clone, Hash, Eq, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct Holiday {
+    pub name: String,
+    pub first_day: Date,
+    pub last_day: Date,
+}
+
+impl Holiday {
+    pub fn new(name: &str, first_day: &Date, last_day: &Date) -> Self {
+        Holiday {
+            name: name.to_owned(),
+            first_day: *first_day,
+            last_day: *last_day,
+        }
+    }
+
+    pub fn contains(&self, date: &Date) -> bool {
+        (self.first_day..=self.last_day).contains(date)
+    }
+}
+
+impl From<&CalendarEvent> for Holiday {
+    fn from(event: &CalendarEvent) -> Self {
+        Holiday::new(&event.title, &event.first_day(), &event.last_day())
+    }
+}
+
+#[derive(Debug, Clone, Hash, Eq, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct Exam {
+    pub event: CalendarEvent,
+    pub lessons: Vec<Lesson>,
+}
+
+#[derive(Debug, Clone, Hash, Eq, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
+#[allow(clippy::large_enum_variant)]
+pub enum ScheduleEntry {
+    Lesson { lesson: Lesson, on_holiday: bool },
+    Exam(Exam),
+    Event(CalendarEvent),
+}
+
+impl ScheduleEntry {
+    pub fn begins_at(&self) -> u64 {
+        match self {
+            ScheduleEntry::Lesson { lesson, .. } => lesson.begins_at,
+            ScheduleEntry::Exam(exam) => exam.event.begins_at,
+            ScheduleEntry::Event(event) => event.begins_at,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Hash, Eq, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct ScheduleDay {
+    pub date: Date,
+    pub holiday: Option<Holiday>,
+    pub entries: Vec<ScheduleEntry>,
+}
+
+#[derive(Debug, Clone, Hash, Eq, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct Schedule {
+    pub days: Vec<ScheduleDay>,
+}
+
+impl Schedule {
+    pub fn new(
+        lessons: &[Lesson],
+        events: &[CalendarEvent],
+        holidays: &[Holiday],
+        begin: &Date,
+        end: &Date,
+    ) -> Self {
+        let holidays: Vec<Holiday> = holidays
+            .iter()
+            .cloned()
+            .chain(
+                events
+                    .iter()
+                    .filter(|event| event.kind == CalendarEventKind::HOLIDAY)
+                    .map(Holiday::from),
+            )
+            .collect();
+        let days = begin
+            .range_to(end)
+            .map(|date| {
+                let holiday = holidays.iter().find(|holiday| holiday.contains(&date));
+                let mut entries: Vec<ScheduleEntry> = lessons
+                    .iter()
+                    .filter(|lesson| Date::from_timestamp(lesson.begins_at) == date)
+                    .map(|lesson| ScheduleEntry::Lesson {
+                        lesson: lesson.clone(),
+                        on_holiday: holiday.is_some(),
+                    })
+                    .collect();
+                for event in events
+                    .iter()
+                    .filter(|event| (event.first_day()..=event.last_day()).contains(&date))
+                {
+                    match event.kind {
+                        CalendarEventKind::HOLIDAY => {}
+                        // Multi-day exams are listed once, on their first day in range.
+                        CalendarEventKind::EXAM if date != event.first_day().max(*begin) => {}
+                        CalendarEventKind::EXAM => entries.push(ScheduleEntry::Exam(Exam {
+                            event: event.clone(),
+                            lessons: lessons
+                                .iter()
+                                .filter(|lesson| {
+                                    lesson.begins_at < event.ends_at
+                                        && event.begins_at < lesson.ends_at
+                                })
+                                .cloned()
+                                .collect(),
+                        })),
+                        _ => entries.push(ScheduleEntry::Event(event.clone())),
+                    }
+                }
+                entries.sort_by_key(ScheduleEntry::begins_at);
+                ScheduleDay {
+                    date,
+                    holiday: holiday.cloned(),
+                    entries,
+                }
+            })
+            .collect();
+        Schedule { days }
+    }
+
+    pub fn exams(&self) -> impl Iterator<Item = &Exam> {
+        self.days
+            .iter()
+            .flat_map(|day| &day.entries)
+            .filter_map(|entry| match entry {
+                ScheduleEntry::Exam(exam) => Some(exam),
+                _ => None,
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::timetable::tests::lesson;
+
+    #[test]
+    fn test_schedule() {
+        let monday = Date::new(8, 5, 2023).unwrap();
+        let tuesday = monday.add_days(1);
+        let lessons = [
+            lesson(1, monday.to_timestamp() + 28800, LessonKind::NORMAL),
+            lesson(2, tuesday.to_timestamp() + 28800, LessonKind::NORMAL),
+        ];
+        let exam = CalendarEvent {
+            id: 1,
+            calendar_id: 1,
+            title: "Klassenarbeit".to_owned(),
+            description: None,
+            location: None,
+            begins_at: monday.to_timestamp() + 28800,
+            ends_at: tuesday.to_timestamp() + 28800 + 2700,
+            is_all_day: false,
+            kind: CalendarEventKind::EXAM,
+        };
+        let holidays = [Holiday::new("Brückentag", &tuesday, &tuesday)];
+
+        let schedule = Schedule::new(&lessons, &[exam], &holidays, &monday, &tuesday);
+        assert_eq!(schedule.days.len(), 2);
+        assert!(schedule.days[0].holiday.is_none());
+        assert_eq!(schedule.days[0].entries.len(), 2);
+        assert_eq!(schedule.exams().count(), 1);
+        assert_eq!(schedule.exams().next().unwrap().lessons.len(), 2);
+        assert_eq!(
+            schedule.days[1].holiday.as_ref().unwrap().name,
+            "Brückentag"
+        );
+        assert!(matches!(
+            schedule.days[1].entries[0],
+            ScheduleEntry::Lesson {
+                on_holiday: true,
+                ..
+            }
+        ));
+    }
+}
@@ -0,0 +1,357 @@
+use std::collections::BTreeMap;
+
+use crate::timetable::*;
+
+pub async fn get_substitution_plan(
+    token: &str,
+    begin: &Date,
+    end: &Date,
+) -> SduiResult<SubstitutionPlan> {
+    let (grades, mut rate_limit) = get_grades(token).await?;
+    let mut lessons: BTreeMap<u64, Lesson> = BTreeMap::new();
+    for grade in &grades {
+        let (timetable, grade_rate_limit) =
+            get_target_timetable(token, &grade.into(), begin, end).await?;
+        rate_limit = rate_limit.join(grade_rate_limit);
+        lessons.extend(
+            timetable
+                .lessons
+                .into_iter()
+                .map(|lesson| (lesson.id, lesson)),
+        );
+    }
+    let lessons: Vec<Lesson> = lessons.into_values().collect();
+    Ok((SubstitutionPlan::from_lessons(&lessons), rate_limit))
+}
+
+#[derive(Debug, Clone, Hash, Eq, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct SubstitutionPlan {
+    pub days: Vec<SubstitutionDay>,
+}
+
+#[derive(Debug, Clone, Hash, Eq, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct SubstitutionDay {
+    pub date: Date,
+    pub entries: Vec<Substitution>,
+}
+
+#[derive(Debug, Clone, Hash, Eq, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct Substitution {
+    pub lesson_id: u64,
+    pub original_lesson_id: Option<u64>,
+    pub begins_at: u64,
+    pub ends_at: u64,
+    pub period: String,
+    pub kind: LessonKind,
+    pub grades: Vec<Grade>,
+    pub course: Course,
+    pub original_teachers: Vec<Teacher>,
+    pub teachers: Vec<Teacher>,
+    pub original_rooms: Vec<Bookable>,
+    pub rooms: Vec<Bookable>,
+    pub comment: String,
+}
+
+impl Substitution {
+    fn new(lesson: &Lesson, original: Option<&Lesson>) -> Self {
+        let comment = match original {
+            Some(original) if lesson.comment.is_empty() => original.comment.clone(),
+            _ => lesson.comment.clone(),
+        };
+        let (original_teachers, teachers, original_rooms, rooms) = match (&lesson.kind, original) {
+            (LessonKind::CANCLED, _) => (
+                lesson.teachers.clone(),
+                vec![],
+                lesson.bookables.clone(),
+                vec![],
+            ),
+            (_, Some(original)) => (
+                original.teachers.clone(),
+                lesson.teachers.clone(),
+                original.bookables.clone(),
+                lesson.bookables.clone(),
+            ),
+            (_, None) => (
+                vec![],
+                lesson.teachers.clone(),
+                vec![],
+                lesson.bookables.clone(),
+            ),
+        };
+        Substitution {
+            lesson_id: lesson.id,
+            original_lesson_id: original.map(|original| original.id),
+            begins_at: lesson.begins_at,
+            ends_at: lesson.ends_at,
+            period: lesson.meta.displayname_hour.clone(),
+            kind: lesson.kind.clone(),
+            grades: lesson.grades.clone(),
+            course: lesson.course.clone(),
+            original_teachers,
+            teachers,
+            original_rooms,
+            rooms,
+            comment,
+        }
+    }
+
+    fn grades_cell(&self) -> String {
+        self.grades
+            .iter()
+            .map(|grade| grade.shortcut.as_str())
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    fn teachers_cell(&self) -> String {
+        change_cell(
+            &self
+                .original_teachers
+                .iter()
+                .map(|teacher| teacher.shortcut.as_str())
+                .collect::<Vec<_>>(),
+            &self
+                .teachers
+                .iter()
+                .map(|teacher| teacher.shortcut.as_str())
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    fn rooms_cell(&self) -> String {
+        change_cell(
+            &self
+                .original_rooms
+                .iter()
+                .map(|room| room.shortcut.as_str())
+                .collect::<Vec<_>>(),
+            &self
+                .rooms
+                .iter()
+                .map(|room| room.shortcut.as_str())
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    fn kind_cell(&self) -> &str {
+        match &self.kind {
+            LessonKind::NORMAL => "",
+            LessonKind::SUBSTITUTION => "substitution",
+            LessonKind::CANCLED => "cancelled",
+            LessonKind::ADDITIONAL => "additional",
+            LessonKind::Other(kind) => kind,
+        }
+    }
+
+    fn kind_class(&self) -> &'static str {
+        match &self.kind {
+            LessonKind::NORMAL => "",
+            LessonKind::SUBSTITUTION => "substitution",
+            LessonKind::CANCLED => "cancelled",
+            LessonKind::ADDITIONAL => "additional",
+            LessonKind::Other(_) => "other",
+        }
+    }
+
+    fn cells(&self) -> [String; 7] {
+        [
+            self.grades_cell(),
+            self.period.clone(),
+            self.course.meta.shortname.clone(),
+            self.teachers_cell(),
+            self.rooms_cell(),
+            self.kind_cell().to_owned(),
+            self.comment.clone(),
+        ]
+    }
+}
+
+fn change_cell(original: &[&str], current: &[&str]) -> String {
+    let original = original.join(", ");
+    let current = current.join(", ");
+    if original.is_empty() || original == current {
+        current
+    } else if current.is_empty() {
+        original
+    } else {
+        format!("{} → {}", original, current)
+    }
+}
+
+fn shares_grade(a: &Lesson, b: &Lesson) -> bool {
+    a.grades.is_empty() && b.grades.is_empty()
+        || a.grades
+            .iter()
+            .any(|grade| b.grades.iter().any(|other| other.id == grade.id))
+}
+
+const HEADERS: [&str; 7] = [
+    "Grade", "Period", "Course", "Teacher", "Room", "Kind", "Comment",
+];
+
+impl SubstitutionPlan {
+    pub fn from_lessons(lessons: &[Lesson]) -> Self {
+        let mut changed: Vec<&Lesson> = lessons
+            .iter()
+            .filter(|lesson| lesson.kind != LessonKind::NORMAL)
+            .collect();
+        changed.sort_by_key(|lesson| (lesson.begins_at, lesson.id));
+        let (cancelled, replacements): (Vec<&Lesson>, Vec<&Lesson>) = changed
+            .into_iter()
+            .partition(|lesson| lesson.kind == LessonKind::CANCLED);
+        let mut paired = vec![false; cancelled.len()];
+        let mut entries = vec![];
+        for lesson in replacements {
+            let original = cancelled
+                .iter()
+                .enumerate()
+                .filter(|(index, cancelled)| {
+                    !paired[*index]
+                        && cancelled.begins_at == lesson.begins_at
+                        && shares_grade(cancelled, lesson)
+                })
+                .min_by_key(|(_, cancelled)| cancelled.course.id != lesson.course.id)
+                .map(|(index, cancelled)| {
+                    paired[index] = true;
+                    *cancelled
+                });
+            entries.push(Substitution::new(lesson, original));
+        }
+        entries.extend(
+            cancelled
+                .iter()
+                .zip(paired)
+                .filter(|(_, paired)| !paired)
+                .map(|(lesson, _)| Substitution::new(lesson, None)),
+        );
+
+        let mut days: BTreeMap<u64, Vec<Substitution>> = BTreeMap::new();
+        for entry in entries {
+            days.entry(entry.begins_at / 86400).or_default().push(entry);
+        }
+        SubstitutionPlan {
+            days: days
+                .into_iter()
+                .map(|(day, mut entries)| {
+                    entries.sort_by_cached_key(|entry| (entry.grades_cell(), entry.begins_at));
+                    SubstitutionDay {
+                        date: Date::from_timestamp(day * 86400),
+                        entries,
+                    }
+                })
+                .collect(),
+        }
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+        for day in &self.days {
+            let rows: Vec<[String; 7]> = day.entries.iter().map(Substitution::cells).collect();
+            let mut widths = HEADERS.map(|header| header.chars().count());
+            for row in &rows {
+                for (width, cell) in widths.iter_mut().zip(row) {
+                    *width = (*width).max(cell.chars().count());
+                }
+            }
+            text.push_str(&format!("{}\n", day.date));
+            let header = HEADERS.map(|header| header.to_owned());
+            for row in std::iter::once(&header).chain(&rows) {
+                let line = row
+                    .iter()
+                    .zip(widths)
+                    .map(|(cell, width)| {
+                        format!("{}{}", cell, " ".repeat(width - cell.chars().count()))
+                    })
+                    .collect::<Vec<_>>()
+                    .join("  ");
+                text.push_str(line.trim_end());
+                text.push('\n');
+            }
+            text.push('\n');
+        }
+        text
+    }
+
+    pub fn to_html(&self) -> String {
+        let mut html = String::from(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Substitution plan</title>\n<style>\nbody { font-family: sans-serif; }\ntable { border-collapse: collapse; margin-bottom: 2em; }\nth, td { border: 1px solid #ccc; padding: 0.3em 0.6em; text-align: left; }\ntr.cancelled td { text-decoration: line-through; color: #a00; }\ntr.substitution td, tr.additional td { color: #05a; }\n</style>\n</head>\n<body>\n",
+        );
+        for day in &self.days {
+            html.push_str(&format!("<h2>{}</h2>\n<table>\n<tr>", day.date));
+            for header in HEADERS {
+                html.push_str(&format!("<th>{}</th>", header));
+            }
+            html.push_str("</tr>\n");
+            for entry in &day.entries {
+                html.push_str(&format!("<tr class=\"{}\">", entry.kind_class()));
+                for cell in entry.cells() {
+                    html.push_str(&format!("<td>{}</td>", escape_html(&cell)));
+                }
+                html.push_str("</tr>\n");
+            }
+            html.push_str("</table>\n");
+        }
+        html.push_str("</body>\n</html>\n");
+        html
+    }
+
+    pub fn to_json(&self) -> Result<String, SduiError> {
+        serde_json::to_string_pretty(self).map_err(|_| SduiError::JSONError)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::timetable::tests::lesson;
+
+    fn teacher(id: u64, shortcut: &str) -> Teacher {
+        Teacher {
+            id,
+            name: shortcut.to_owned(),
+            shortcut: shortcut.to_owned(),
+        }
+    }
+
+    #[test]
+    fn test_substitution_pairing() {
+        let grade = Grade {
+            id: 1,
+            name: "5a".to_owned(),
+            shortcut: "5a".to_owned(),
+        };
+        let mut cancelled = lesson(1, 86400 + 28800, LessonKind::CANCLED);
+        cancelled.grades = vec![grade.clone()];
+        cancelled.teachers = vec![teacher(1, "MUE")];
+        let mut substitution = lesson(2, 86400 + 28800, LessonKind::SUBSTITUTION);
+        substitution.grades = vec![grade.clone()];
+        substitution.teachers = vec![teacher(2, "SCH")];
+        let mut dropped = lesson(3, 86400 + 32400, LessonKind::CANCLED);
+        dropped.grades = vec![grade];
+        dropped.teachers = vec![teacher(1, "MUE")];
+        let normal = lesson(4, 86400 + 36000, LessonKind::NORMAL);
+
+        let plan = SubstitutionPlan::from_lessons(&[normal, dropped, substitution, cancelled]);
+        assert_eq!(plan.days.len(), 1);
+        assert_eq!(plan.days[0].date, Date::new(2, 1, 1970).unwrap());
+        let entries = &plan.days[0].entries;
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].lesson_id, 2);
+        assert_eq!(entries[0].teachers_cell(), "MUE → SCH");
+        assert_eq!(entries[1].lesson_id, 3);
+        assert_eq!(entries[1].teachers_cell(), "MUE");
+        assert_eq!(entries[1].kind_cell(), "cancelled");
+
+        let mut exam = entries[1].clone();
+        exam.kind = LessonKind::Other("\"><script>".to_owned());
+        let html = SubstitutionPlan {
+            days: vec![SubstitutionDay {
+                date: plan.days[0].date,
+                entries: vec![exam],
+            }],
+        }
+        .to_html();
+        assert!(html.contains("<tr class=\"other\"><td>"));
+        assert!(!html.contains("<script>"));
+    }
+}
@@ -0,0 +1,296 @@
+use std::{
+    collections::VecDeque,
+    path::PathBuf,
+    sync::atomic::{AtomicU64, Ordering},
+    time::Duration,
+};
+
+use futures::{future::BoxFuture, stream, FutureExt, Stream};
+use reqwest::StatusCode;
+
+use crate::timetable::*;
+
+const MAX_BACKOFF: Duration = Duration::from_secs(3600);
+
+static TEMP_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+pub struct TimeTableWatcher {
+    token: String,
+    target: TimetableTarget,
+    days: u64,
+    interval: Duration,
+    snapshot: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Hash, Eq, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct TimeTableSnapshot {
+    pub first_day: Date,
+    pub last_day: Date,
+    pub timetable: TimeTable,
+}
+
+impl TimeTableSnapshot {
+    fn lessons_between(&self, first_day: Date, last_day: Date) -> Vec<Lesson> {
+        self.timetable
+            .lessons
+            .iter()
+            .filter(|lesson| {
+                (first_day..=last_day).contains(&Date::from_timestamp(lesson.begins_at))
+            })
+            .cloned()
+            .collect()
+    }
+
+    pub fn changes(&self, newer: &TimeTableSnapshot) -> Vec<TimeTableChange> {
+        let first_day = self.first_day.max(newer.first_day);
+        let last_day = self.last_day.min(newer.last_day);
+        diff(
+            &self.lessons_between(first_day, last_day),
+            &newer.lessons_between(first_day, last_day),
+        )
+    }
+}
+
+struct WatcherState<P> {
+    watcher: TimeTableWatcher,
+    poll: P,
+    snapshot: Option<TimeTableSnapshot>,
+    pending: VecDeque<Result<TimeTableChange, SduiError>>,
+    delay: Option<Duration>,
+}
+
+impl TimeTableWatcher {
+    pub fn new(token: &str, target: TimetableTarget) -> Self {
+        TimeTableWatcher {
+            token: token.to_owned(),
+            target,
+            days: 7,
+            interval: Duration::from_secs(15 * 60),
+            snapshot: None,
+        }
+    }
+
+    pub fn days(mut self, days: u64) -> Self {
+        self.days = days;
+        self
+    }
+
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    pub fn snapshot(mut self, path: impl Into<PathBuf>) -> Self {
+        self.snapshot = Some(path.into());
+        self
+    }
+
+    pub async fn poll(&self) -> SduiResult<TimeTableSnapshot> {
+        poll_snapshot(&self.token, &self.target, self.days).await
+    }
+
+    async fn load_snapshot(&self) -> Option<TimeTableSnapshot> {
+        let data = tokio::fs::read(self.snapshot.as_ref()?).await.ok()?;
+        serde_json::from_slice(&data).ok()
+    }
+
+    async fn save_snapshot(&self, snapshot: &TimeTableSnapshot) -> Result<(), SduiError> {
+        if let Some(path) = &self.snapshot {
+            let data = serde_json::to_vec(snapshot).map_err(|_| SduiError::JSONError)?;
+            let mut temp = path.as_os_str().to_owned();
+            temp.push(format!(
+                ".{}-{}.tmp",
+                std::process::id(),
+                TEMP_COUNTER.fetch_add(1, Ordering::Relaxed)
+            ));
+            let temp = PathBuf::from(temp);
+            let result = match tokio::fs::write(&temp, data).await {
+                Ok(()) => tokio::fs::rename(&temp, path).await,
+                Err(err) => Err(err),
+            };
+            if result.is_err() {
+                let _ = tokio::fs::remove_file(&temp).await;
+            }
+            result.map_err(SduiError::IOError)?;
+        }
+        Ok(())
+    }
+
+    pub fn watch(self) -> impl Stream<Item = Result<TimeTableChange, SduiError>> {
+        let (token, target, days) = (self.token.clone(), self.target.clone(), self.days);
+        self.watch_with(move || {
+            let (token, target) = (token.clone(), target.clone());
+            async move { poll_snapshot(&token, &target, days).await }.boxed()
+        })
+    }
+
+    fn watch_with<P>(self, poll: P) -> impl Stream<Item = Result<TimeTableChange, SduiError>>
+    where
+        P: FnMut() -> BoxFuture<'static, SduiResult<TimeTableSnapshot>>,
+    {
+        let state = WatcherState {
+            watcher: self,
+            poll,
+            snapshot: None,
+            pending: VecDeque::new(),
+            delay: None,
+        };
+        stream::unfold(state, |mut state| async move {
+            loop {
+                if let Some(item) = state.pending.pop_front() {
+                    return Some((item, state));
+                }
+                let interval = state.watcher.interval;
+                let delay = match state.delay {
+                    Some(delay) => {
+                        tokio::time::sleep(delay).await;
+                        delay
+                    }
+                    None => {
+                        state.snapshot = state.watcher.load_snapshot().await;
+                        interval
+                    }
+                };
+                let backoff = (delay * 2).min(MAX_BACKOFF.max(interval));
+                state.delay = Some(interval);
+                let (snapshot, rate_limit) = match (state.poll)().await {
+                    Ok(result) => result,
+                    Err(err) => {
+                        if is_rate_limited(&err) {
+                            state.delay = Some(backoff);
+                        }
+                        state.pending.push_back(Err(err));
+                        continue;
+                    }
+                };
+                if rate_limit.limit > 0 && rate_limit.remaining == 0 {
+                    state.delay = Some(backoff);
+                }
+                if let Some(old) = &state.snapshot {
+                    state
+                        .pending
+                        .extend(old.changes(&snapshot).into_iter().map(Ok));
+                }
+                if state.snapshot.as_ref() != Some(&snapshot) {
+                    if let Err(err) = state.watcher.save_snapshot(&snapshot).await {
+                        state.pending.push_back(Err(err));
+                    }
+                    state.snapshot = Some(snapshot);
+                }
+            }
+        })
+    }
+}
+
+async fn poll_snapshot(
+    token: &str,
+    target: &TimetableTarget,
+    days: u64,
+) -> SduiResult<TimeTableSnapshot> {
+    let first_day = Date::today();
+    let last_day = first_day.add_days(days as i64);
+    let (timetable, rate_limit) =
+        get_target_timetable(token, target, &first_day, &last_day).await?;
+    Ok((
+        TimeTableSnapshot {
+            first_day,
+            last_day,
+            timetable,
+        },
+        rate_limit,
+    ))
+}
+
+fn is_rate_limited(err: &SduiError) -> bool {
+    matches!(err, SduiError::RequestError(err) if err.status() == Some(StatusCode::TOO_MANY_REQUESTS))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::timetable::tests::lesson;
+    use futures::StreamExt;
+
+    const MONDAY: u64 = 1683504000;
+    const DAY: u64 = 86400;
+
+    fn snapshot(lessons: Vec<Lesson>) -> TimeTableSnapshot {
+        TimeTableSnapshot {
+            first_day: Date::from_timestamp(MONDAY),
+            last_day: Date::from_timestamp(MONDAY + 2 * DAY),
+            timetable: TimeTable {
+                lessons,
+                last_updated_at: String::new(),
+            },
+        }
+    }
+
+    #[test]
+    fn test_snapshot_changes() {
+        let old = snapshot(vec![
+            lesson(1, MONDAY + 28800, LessonKind::NORMAL),
+            lesson(2, MONDAY + 2 * DAY + 28800, LessonKind::NORMAL),
+        ]);
+        let mut new = snapshot(vec![
+            lesson(1, MONDAY + 28800, LessonKind::CANCLED),
+            lesson(3, MONDAY + 3 * DAY + 28800, LessonKind::NORMAL),
+        ]);
+        new.first_day = Date::from_timestamp(MONDAY + DAY);
+        new.last_day = Date::from_timestamp(MONDAY + 3 * DAY);
+        assert_eq!(
+            old.changes(&new),
+            vec![TimeTableChange::Removed(lesson(
+                2,
+                MONDAY + 2 * DAY + 28800,
+                LessonKind::NORMAL
+            ))]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_watch() {
+        let path =
+            std::env::temp_dir().join(format!("rust_sdui_watch_{}.json", std::process::id()));
+        let _ = tokio::fs::remove_file(&path).await;
+        let watcher = || {
+            TimeTableWatcher::new("", TimetableTarget::User(1))
+                .interval(Duration::from_millis(1))
+                .snapshot(&path)
+        };
+        let normal = snapshot(vec![lesson(1, MONDAY + 28800, LessonKind::NORMAL)]);
+        let cancelled = snapshot(vec![lesson(1, MONDAY + 28800, LessonKind::CANCLED)]);
+
+        let mut polls = VecDeque::from([normal.clone(), cancelled.clone()]);
+        let changes: Vec<_> = watcher()
+            .watch_with(move || {
+                let snapshot = polls.pop_front().unwrap_or_else(|| cancelled.clone());
+                async move { Ok((snapshot, RateLimit::unknown())) }.boxed()
+            })
+            .take(1)
+            .collect()
+            .await;
+        assert_eq!(
+            changes.into_iter().map(Result::unwrap).collect::<Vec<_>>(),
+            vec![TimeTableChange::Changed {
+                old: lesson(1, MONDAY + 28800, LessonKind::NORMAL),
+                new: lesson(1, MONDAY + 28800, LessonKind::CANCLED),
+            }]
+        );
+
+        let saved = watcher().load_snapshot().await.unwrap();
+        assert_eq!(saved.timetable.lessons[0].kind, LessonKind::CANCLED);
+        let mut polls = VecDeque::from([normal]);
+        let change = watcher()
+            .watch_with(move || {
+                let snapshot = polls.pop_front().unwrap();
+                async move { Ok((snapshot, RateLimit::unknown())) }.boxed()
+            })
+            .boxed()
+            .next()
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(change.lesson().kind, LessonKind::NORMAL);
+        tokio::fs::remove_file(&path).await.unwrap();
+    }
+}
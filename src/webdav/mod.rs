@@ -0,0 +1,684 @@
+use std::{convert::Infallible, net::SocketAddr, sync::Arc};
+
+use futures::StreamExt;
+use hyper::{
+    header::{
+        HeaderValue, ALLOW, AUTHORIZATION, CONTENT_LENGTH, CONTENT_TYPE, HOST, LOCATION,
+        WWW_AUTHENTICATE,
+    },
+    service::{make_service_fn, service_fn},
+    Body, Method, Request, Response, Server, StatusCode,
+};
+use tokio::sync::Mutex;
+use tokio_util::io::StreamReader;
+
+use crate::{
+    cloud::{Cloud, CloudTree, UploadRequest},
+    files::{percent_decode, File},
+    prelude::*,
+    timetable::Date,
+};
+
+const ALLOWED: &str = "OPTIONS, PROPFIND, GET, HEAD, PUT, MKCOL, MOVE, DELETE";
+const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+const WEEKDAYS: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+pub struct WebDavServer {
+    token: String,
+    clouds: Vec<(String, Cloud)>,
+    credentials: Option<String>,
+}
+
+struct State {
+    token: String,
+    clouds: Mutex<Vec<(String, CloudTree)>>,
+    hosts: Vec<String>,
+    authorization: Option<String>,
+}
+
+#[allow(clippy::large_enum_variant)]
+enum Target {
+    Root,
+    Cloud(usize),
+    Entry(usize, File),
+}
+
+impl WebDavServer {
+    pub fn new(token: &str) -> Self {
+        WebDavServer {
+            token: token.to_owned(),
+            clouds: vec![],
+            credentials: None,
+        }
+    }
+
+    pub fn credentials(mut self, user: &str, password: &str) -> Self {
+        self.credentials = Some(format!("{}:{}", user, password));
+        self
+    }
+
+    pub fn cloud(mut self, name: &str, cloud: &Cloud) -> Self {
+        let mut name = name.replace('/', "_");
+        if self.clouds.iter().any(|(existing, _)| *existing == name) {
+            name = format!("{} ({})", name, cloud.id);
+        }
+        self.clouds.push((name, cloud.clone()));
+        self
+    }
+
+    pub async fn serve(self, port: u16) -> Result<(), SduiError> {
+        let state = Arc::new(State {
+            token: self.token,
+            clouds: Mutex::new(
+                self.clouds
+                    .into_iter()
+                    .map(|(name, cloud)| (name, CloudTree::new(&cloud)))
+                    .collect(),
+            ),
+            hosts: hosts(port),
+            authorization: self
+                .credentials
+                .map(|credentials| format!("Basic {}", base64(credentials.as_bytes()))),
+        });
+        let service = make_service_fn(move |_| {
+            let state = state.clone();
+            async move {
+                Ok::<_, Infallible>(service_fn(move |request| {
+                    let state = state.clone();
+                    async move { Ok::<_, Infallible>(state.handle(request).await) }
+                }))
+            }
+        });
+        Server::bind(&SocketAddr::from(([127, 0, 0, 1], port)))
+            .serve(service)
+            .await
+            .map_err(|err| SduiError::IOError(std::io::Error::other(err)))
+    }
+}
+
+impl State {
+    async fn handle(&self, request: Request<Body>) -> Response<Body> {
+        if let Err(status) = self.check(&request) {
+            let mut response = Response::builder().status(status);
+            if status == StatusCode::UNAUTHORIZED {
+                response =
+                    response.header(WWW_AUTHENTICATE, "Basic realm=\"sdui\", charset=\"UTF-8\"");
+            }
+            return response.body(Body::empty()).unwrap_or_default();
+        }
+        let path = percent_decode(request.uri().path());
+        let result = match request.method().as_str() {
+            "OPTIONS" => Ok(Response::builder()
+                .header("DAV", "1")
+                .header(ALLOW, ALLOWED)
+                .body(Body::empty())
+                .unwrap_or_default()),
+            "PROPFIND" => self.propfind(&path, &request).await,
+            "GET" | "HEAD" => self.get(&path, request.method() == Method::HEAD).await,
+            "PUT" => self.put(&path, request).await,
+            "MKCOL" => self.mkcol(&path).await,
+            "MOVE" => self.r#move(&path, &request).await,
+            "DELETE" => self.delete(&path).await,
+            _ => Err(StatusCode::METHOD_NOT_ALLOWED),
+        };
+        result.unwrap_or_else(|status| {
+            Response::builder()
+                .status(status)
+                .header(ALLOW, ALLOWED)
+                .body(Body::empty())
+                .unwrap_or_default()
+        })
+    }
+
+    fn check(&self, request: &Request<Body>) -> Result<(), StatusCode> {
+        let host = request
+            .headers()
+            .get(HOST)
+            .and_then(|host| host.to_str().ok())
+            .map(|host| host.to_ascii_lowercase());
+        if !host.is_some_and(|host| self.hosts.contains(&host)) {
+            return Err(StatusCode::FORBIDDEN);
+        }
+        let Some(authorization) = &self.authorization else {
+            return Ok(());
+        };
+        let given = request
+            .headers()
+            .get(AUTHORIZATION)
+            .map_or(&[][..], |given| given.as_bytes());
+        if constant_time_eq(given, authorization.as_bytes()) {
+            Ok(())
+        } else {
+            Err(StatusCode::UNAUTHORIZED)
+        }
+    }
+
+    async fn invalidate(&self, index: usize) {
+        if let Some((_, tree)) = self.clouds.lock().await.get_mut(index) {
+            tree.invalidate();
+        }
+    }
+
+    async fn target(
+        &self,
+        clouds: &mut [(String, CloudTree)],
+        path: &str,
+    ) -> Result<Target, StatusCode> {
+        let path = path.trim_matches('/');
+        if path.is_empty() {
+            return Ok(Target::Root);
+        }
+        let (name, path) = path.split_once('/').unwrap_or((path, ""));
+        let index = clouds
+            .iter()
+            .position(|(cloud, _)| cloud == name)
+            .ok_or(StatusCode::NOT_FOUND)?;
+        if path.is_empty() {
+            return Ok(Target::Cloud(index));
+        }
+        clouds[index]
+            .1
+            .resolve(&self.token, path)
+            .await
+            .map_err(status)?
+            .0
+            .map(|file| Target::Entry(index, file))
+            .ok_or(StatusCode::NOT_FOUND)
+    }
+
+    async fn parent(
+        &self,
+        clouds: &mut [(String, CloudTree)],
+        path: &str,
+    ) -> Result<(usize, Option<File>, String), StatusCode> {
+        let (parent, name) = path
+            .trim_end_matches('/')
+            .rsplit_once('/')
+            .ok_or(StatusCode::CONFLICT)?;
+        match self.target(clouds, parent).await {
+            Ok(Target::Cloud(index)) => Ok((index, None, name.to_owned())),
+            Ok(Target::Entry(index, folder)) if folder.is_folder() => {
+                Ok((index, Some(folder), name.to_owned()))
+            }
+            Ok(_) | Err(StatusCode::NOT_FOUND) => Err(StatusCode::CONFLICT),
+            Err(status) => Err(status),
+        }
+    }
+
+    async fn propfind(
+        &self,
+        path: &str,
+        request: &Request<Body>,
+    ) -> Result<Response<Body>, StatusCode> {
+        let depth = request
+            .headers()
+            .get("Depth")
+            .and_then(|depth| depth.to_str().ok())
+            .unwrap_or("1");
+        let mut clouds = self.clouds.lock().await;
+        let mut responses = vec![];
+        match self.target(&mut clouds, path).await? {
+            Target::Root => {
+                responses.push(collection("/", ""));
+                if depth != "0" {
+                    for (name, _) in clouds.iter() {
+                        responses.push(collection(&format!("/{}/", name), name));
+                    }
+                }
+            }
+            Target::Cloud(index) => {
+                let name = clouds[index].0.clone();
+                let base = format!("/{}/", name);
+                responses.push(collection(&base, &name));
+                if depth != "0" {
+                    let files = clouds[index]
+                        .1
+                        .list(&self.token, "")
+                        .await
+                        .map_err(status)?
+                        .0
+                        .unwrap_or_default();
+                    responses.extend(files.iter().map(|file| entry(&base, file)));
+                }
+            }
+            Target::Entry(index, file) => {
+                let path = path.trim_matches('/');
+                let base = format!("/{}/", path.rsplit_once('/').map_or("", |(base, _)| base));
+                responses.push(entry(&base, &file));
+                if depth != "0" && file.is_folder() {
+                    let files = clouds[index]
+                        .1
+                        .list(&self.token, inner(path))
+                        .await
+                        .map_err(status)?
+                        .0
+                        .unwrap_or_default();
+                    let base = format!("/{}/", path);
+                    responses.extend(files.iter().map(|file| entry(&base, file)));
+                }
+            }
+        }
+        Response::builder()
+            .status(StatusCode::MULTI_STATUS)
+            .header(CONTENT_TYPE, "application/xml; charset=utf-8")
+            .body(Body::from(multistatus(&responses)))
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+    }
+
+    async fn get(&self, path: &str, head: bool) -> Result<Response<Body>, StatusCode> {
+        let file = match self.target(&mut self.clouds.lock().await, path).await? {
+            Target::Entry(_, file) if !file.is_folder() => file,
+            _ => return Err(StatusCode::METHOD_NOT_ALLOWED),
+        };
+        let download = file.open(&self.token).await.map_err(status)?;
+        let mut response = Response::builder();
+        if let Some(content_type) = download
+            .content_type
+            .as_deref()
+            .or(file.mime_type.as_deref())
+        {
+            response = response.header(CONTENT_TYPE, content_type);
+        }
+        if let Some(length) = download.content_length {
+            response = response.header(CONTENT_LENGTH, length);
+        }
+        let body = if head {
+            Body::empty()
+        } else {
+            Body::wrap_stream(
+                download
+                    .stream
+                    .map(|chunk| chunk.map_err(|err| std::io::Error::other(format!("{:?}", err)))),
+            )
+        };
+        response
+            .body(body)
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+    }
+
+    async fn put(&self, path: &str, request: Request<Body>) -> Result<Response<Body>, StatusCode> {
+        let size = request
+            .headers()
+            .get(CONTENT_LENGTH)
+            .and_then(|length| length.to_str().ok())
+            .and_then(|length| length.parse::<u64>().ok())
+            .ok_or(StatusCode::LENGTH_REQUIRED)?;
+        let (index, cloud, parent, name, existing) = {
+            let mut clouds = self.clouds.lock().await;
+            let (index, parent, name) = self.parent(&mut clouds, path).await?;
+            let tree = &mut clouds[index].1;
+            writable(&tree.cloud)?;
+            let (existing, _) = tree
+                .resolve(&self.token, inner(path))
+                .await
+                .map_err(status)?;
+            (index, tree.cloud.clone(), parent, name, existing)
+        };
+        if existing.as_ref().is_some_and(File::is_folder) {
+            return Err(StatusCode::METHOD_NOT_ALLOWED);
+        }
+        let mut upload = UploadRequest::new(&self.token, &cloud);
+        if let Some(parent) = parent {
+            upload = upload.parent(parent);
+        }
+        let body = request
+            .into_body()
+            .map(|chunk| chunk.map_err(std::io::Error::other));
+        let mut result = upload
+            .upload_reader(&name, StreamReader::new(body), size)
+            .await
+            .map(|_| ());
+        if let (Ok(()), Some(existing)) = (&result, &existing) {
+            result = existing.trash(&self.token).await.map(|_| ());
+        }
+        self.invalidate(index).await;
+        result.map_err(status)?;
+        empty(if existing.is_some() {
+            StatusCode::NO_CONTENT
+        } else {
+            StatusCode::CREATED
+        })
+    }
+
+    async fn mkcol(&self, path: &str) -> Result<Response<Body>, StatusCode> {
+        let (index, cloud, parent, name) = {
+            let mut clouds = self.clouds.lock().await;
+            if self.target(&mut clouds, path).await.is_ok() {
+                return Err(StatusCode::METHOD_NOT_ALLOWED);
+            }
+            let (index, parent, name) = self.parent(&mut clouds, path).await?;
+            writable(&clouds[index].1.cloud)?;
+            (index, clouds[index].1.cloud.clone(), parent, name)
+        };
+        let result = cloud
+            .create_folder(&self.token, &name, parent.as_ref(), false)
+            .await;
+        self.invalidate(index).await;
+        result.map_err(status)?;
+        empty(StatusCode::CREATED)
+    }
+
+    async fn r#move(
+        &self,
+        path: &str,
+        request: &Request<Body>,
+    ) -> Result<Response<Body>, StatusCode> {
+        let destination = request
+            .headers()
+            .get("Destination")
+            .and_then(|destination| destination.to_str().ok())
+            .map(destination_path)
+            .ok_or(StatusCode::BAD_REQUEST)?;
+        let overwrite = overwrite(request);
+        let (index, file, parent, name, existing) = {
+            let mut clouds = self.clouds.lock().await;
+            let Target::Entry(index, file) = self.target(&mut clouds, path).await? else {
+                return Err(StatusCode::FORBIDDEN);
+            };
+            writable(&clouds[index].1.cloud)?;
+            let (destination_index, parent, name) = self.parent(&mut clouds, &destination).await?;
+            if destination_index != index {
+                return Err(StatusCode::FORBIDDEN);
+            }
+            let existing = match self.target(&mut clouds, &destination).await {
+                Ok(Target::Entry(_, existing)) if existing.uuid != file.uuid => Some(existing),
+                _ => None,
+            };
+            if existing.is_some() && !overwrite {
+                return Err(StatusCode::PRECONDITION_FAILED);
+            }
+            (index, file, parent, name, existing)
+        };
+        let mut result = Ok(());
+        if let Some(existing) = &existing {
+            result = existing.trash(&self.token).await.map(|_| ());
+        }
+        if result.is_ok() {
+            result = self.move_file(&file, parent.as_ref(), &name).await;
+        }
+        self.invalidate(index).await;
+        result.map_err(status)?;
+        Response::builder()
+            .status(if existing.is_some() {
+                StatusCode::NO_CONTENT
+            } else {
+                StatusCode::CREATED
+            })
+            .header(
+                LOCATION,
+                HeaderValue::from_str(&percent_encode(&destination))
+                    .map_err(|_| StatusCode::BAD_REQUEST)?,
+            )
+            .body(Body::empty())
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+    }
+
+    async fn move_file(
+        &self,
+        file: &File,
+        parent: Option<&File>,
+        name: &str,
+    ) -> Result<(), SduiError> {
+        let mut moved = file.clone();
+        if parent.map_or("", |parent| parent.uuid.as_str()) != file.parent_id {
+            moved = moved.move_to(&self.token, parent).await?.0;
+        }
+        if name != file.name {
+            moved.rename(&self.token, name).await?;
+        }
+        Ok(())
+    }
+
+    async fn delete(&self, path: &str) -> Result<Response<Body>, StatusCode> {
+        let (index, file) = {
+            let mut clouds = self.clouds.lock().await;
+            let Target::Entry(index, file) = self.target(&mut clouds, path).await? else {
+                return Err(StatusCode::FORBIDDEN);
+            };
+            writable(&clouds[index].1.cloud)?;
+            (index, file)
+        };
+        let result = file.trash(&self.token).await;
+        self.invalidate(index).await;
+        result.map_err(status)?;
+        empty(StatusCode::NO_CONTENT)
+    }
+}
+
+fn writable(cloud: &Cloud) -> Result<(), StatusCode> {
+    if cloud.can.upload == 0 {
+        Err(StatusCode::FORBIDDEN)
+    } else {
+        Ok(())
+    }
+}
+
+fn empty(status: StatusCode) -> Result<Response<Body>, StatusCode> {
+    Response::builder()
+        .status(status)
+        .body(Body::empty())
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+fn status(err: SduiError) -> StatusCode {
+    match err {
+        SduiError::NotLoggedIn => StatusCode::UNAUTHORIZED,
+        SduiError::PermissionDenied => StatusCode::FORBIDDEN,
+        SduiError::InvalidName(_) => StatusCode::BAD_REQUEST,
+        SduiError::InvalidTarget(_) => StatusCode::CONFLICT,
+        SduiError::ForbiddenExtension(_) => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+        SduiError::FileTooLarge { .. } => StatusCode::PAYLOAD_TOO_LARGE,
+        SduiError::IOError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        _ => StatusCode::BAD_GATEWAY,
+    }
+}
+
+fn base64(data: &[u8]) -> String {
+    let mut encoded = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let bytes = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let group = u32::from_be_bytes([0, bytes[0], bytes[1], bytes[2]]);
+        for index in 0..4 {
+            if index <= chunk.len() {
+                encoded.push(BASE64[(group >> (18 - 6 * index) & 63) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+fn constant_time_eq(given: &[u8], expected: &[u8]) -> bool {
+    given.len() == expected.len()
+        && given
+            .iter()
+            .zip(expected)
+            .fold(0, |difference, (a, b)| difference | (a ^ b))
+            == 0
+}
+
+fn hosts(port: u16) -> Vec<String> {
+    let mut hosts = vec![format!("localhost:{}", port), format!("127.0.0.1:{}", port)];
+    if port == 80 {
+        hosts.extend(["localhost".to_owned(), "127.0.0.1".to_owned()]);
+    }
+    hosts
+}
+
+fn overwrite(request: &Request<Body>) -> bool {
+    request
+        .headers()
+        .get("Overwrite")
+        .is_none_or(|overwrite| !overwrite.as_bytes().eq_ignore_ascii_case(b"F"))
+}
+
+fn inner(path: &str) -> &str {
+    path.trim_matches('/')
+        .split_once('/')
+        .map_or("", |(_, inner)| inner)
+}
+
+fn destination_path(destination: &str) -> String {
+    let path = destination
+        .split_once("://")
+        .map_or(destination, |(_, rest)| {
+            rest.find('/').map_or("/", |index| &rest[index..])
+        });
+    percent_decode(path)
+}
+
+fn percent_encode(path: &str) -> String {
+    path.bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b'/' => {
+                (byte as char).to_string()
+            }
+            byte => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
+fn http_date(timestamp: &str) -> Option<String> {
+    let date: Date = timestamp.get(..10)?.parse().ok()?;
+    let time = timestamp
+        .get(11..19)
+        .filter(|time| time.len() == 8)
+        .unwrap_or("00:00:00");
+    Some(format!(
+        "{}, {:02} {} {} {} GMT",
+        WEEKDAYS[usize::from(date.weekday() - 1)],
+        date.day(),
+        MONTHS[usize::from(date.month() - 1)],
+        date.year(),
+        time
+    ))
+}
+
+fn collection(href: &str, name: &str) -> String {
+    format!(
+        "<D:response><D:href>{}</D:href><D:propstat><D:prop><D:displayname>{}</D:displayname><D:resourcetype><D:collection/></D:resourcetype></D:prop><D:status>HTTP/1.1 200 OK</D:status></D:propstat></D:response>",
+        escape_html(&percent_encode(href)),
+        escape_html(name)
+    )
+}
+
+fn entry(base: &str, file: &File) -> String {
+    if file.is_folder() {
+        return collection(&format!("{}{}/", base, file.name), &file.name);
+    }
+    let mut properties = format!(
+        "<D:displayname>{}</D:displayname><D:resourcetype/><D:getcontentlength>{}</D:getcontentlength>",
+        escape_html(&file.name),
+        file.size
+    );
+    if let Some(mime_type) = &file.mime_type {
+        properties.push_str(&format!(
+            "<D:getcontenttype>{}</D:getcontenttype>",
+            escape_html(mime_type)
+        ));
+    }
+    if let Some(modified) = http_date(&file.updated_at) {
+        properties.push_str(&format!(
+            "<D:getlastmodified>{}</D:getlastmodified>",
+            modified
+        ));
+    }
+    if let Some(hash) = &file.hash {
+        properties.push_str(&format!("<D:getetag>\"{}\"</D:getetag>", escape_html(hash)));
+    }
+    format!(
+        "<D:response><D:href>{}</D:href><D:propstat><D:prop>{}</D:prop><D:status>HTTP/1.1 200 OK</D:status></D:propstat></D:response>",
+        escape_html(&percent_encode(&format!("{}{}", base, file.name))),
+        properties
+    )
+}
+
+fn multistatus(responses: &[String]) -> String {
+    format!(
+        "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<D:multistatus xmlns:D=\"DAV:\">{}</D:multistatus>\n",
+        responses.concat()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::files::tests::file;
+
+    #[test]
+    fn test_propfind_entries() {
+        let mut worksheet = file("Woche 3.pdf", "file");
+        worksheet.size = 300;
+        assert_eq!(
+            entry("/Klasse 5a/", &worksheet),
+            "<D:response><D:href>/Klasse%205a/Woche%203.pdf</D:href><D:propstat><D:prop><D:displayname>Woche 3.pdf</D:displayname><D:resourcetype/><D:getcontentlength>300</D:getcontentlength><D:getlastmodified>Mon, 08 May 2023 08:00:00 GMT</D:getlastmodified></D:prop><D:status>HTTP/1.1 200 OK</D:status></D:propstat></D:response>"
+        );
+        assert!(entry("/Klasse 5a/", &file("Mathe", "folder"))
+            .contains("<D:href>/Klasse%205a/Mathe/</D:href>"));
+        assert_eq!(
+            destination_path("http://localhost:8080/Klasse%205a/Arbeitsbl%C3%A4tter"),
+            "/Klasse 5a/Arbeitsblätter"
+        );
+    }
+
+    #[test]
+    fn test_request_checks() {
+        assert_eq!(base64(b"lehrer:geheim"), "bGVocmVyOmdlaGVpbQ==");
+        assert_eq!(base64(b"ab"), "YWI=");
+        let state = State {
+            token: String::new(),
+            clouds: Mutex::new(vec![]),
+            hosts: hosts(8080),
+            authorization: Some(format!("Basic {}", base64(b"lehrer:geheim"))),
+        };
+        let request = |host: &str, authorization: &str| {
+            Request::builder()
+                .header(HOST, host)
+                .header(AUTHORIZATION, authorization)
+                .body(Body::empty())
+                .unwrap()
+        };
+        let check = |request: Request<Body>| state.check(&request);
+        assert_eq!(
+            check(request("localhost:8080", "Basic bGVocmVyOmdlaGVpbQ==")),
+            Ok(())
+        );
+        assert_eq!(
+            check(request("evil.example:8080", "Basic bGVocmVyOmdlaGVpbQ==")),
+            Err(StatusCode::FORBIDDEN)
+        );
+        assert_eq!(
+            check(request("127.0.0.1:8080", "Basic d3Jvbmc=")),
+            Err(StatusCode::UNAUTHORIZED)
+        );
+        assert_eq!(
+            check(request("localhost", "Basic bGVocmVyOmdlaGVpbQ==")),
+            Err(StatusCode::FORBIDDEN)
+        );
+        assert!(hosts(80).contains(&"localhost".to_owned()));
+        assert!(hosts(80).contains(&"127.0.0.1:80".to_owned()));
+    }
+
+    #[test]
+    fn test_move_overwrite() {
+        let request = |overwrite: Option<&str>| {
+            let mut request = Request::builder();
+            if let Some(overwrite) = overwrite {
+                request = request.header("Overwrite", overwrite);
+            }
+            request.body(Body::empty()).unwrap()
+        };
+        assert!(overwrite(&request(None)));
+        assert!(overwrite(&request(Some("T"))));
+        assert!(!overwrite(&request(Some("F"))));
+        assert!(!overwrite(&request(Some("f"))));
+    }
+}